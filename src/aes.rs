@@ -2,31 +2,31 @@ use std::io::{Read, Write};
 use crypto::aes::KeySize;
 use crypto::blockmodes::PkcsPadding;
 use crypto::buffer::{BufferResult, ReadBuffer, RefReadBuffer, RefWriteBuffer, WriteBuffer};
-use crypto::symmetriccipher::{Decryptor, Encryptor, SymmetricCipherError};
+use crypto::symmetriccipher::{Decryptor, SymmetricCipherError};
+use zeroize::Zeroize;
 use crate::error::AppError;
+use crate::stream;
+
+const BUFFER_SIZE: usize = 64 * 1024;
 
 pub enum Aes {
-    Encryptor(Box<dyn Encryptor + 'static>),
     Decryptor(Box<dyn Decryptor + 'static>),
 }
 
 impl Aes {
-    pub fn encryptor(iv: &[u8], key: &[u8]) -> Self {
-        Aes::Encryptor(crypto::aes::cbc_encryptor(KeySize::KeySize256, key, iv, PkcsPadding))
-    }
-
     pub fn decryptor(iv: &[u8], key: &[u8]) -> Self {
         Aes::Decryptor(crypto::aes::cbc_decryptor(KeySize::KeySize256, key, iv, PkcsPadding))
     }
 
     pub fn write<I: Read, O: Write>(&mut self, input: &mut I, output: &mut O) -> Result<(), AppError> {
-        let mut in_buf = [0u8; 16];
+        // Output holds a whole input buffer plus a block kept back for padding
+        let mut in_buf = vec![0u8; BUFFER_SIZE];
         let mut in_buf_len;
-        let mut out_buf = [0u8; 32];
+        let mut out_buf = vec![0u8; BUFFER_SIZE + 16];
 
         let mut eof = false;
         while !eof {
-            in_buf_len = input.read(&mut in_buf)?;
+            in_buf_len = stream::read_full(input, &mut in_buf)?;
             eof = in_buf_len < in_buf.len();
 
             let mut in_ref_buf = RefReadBuffer::new(&in_buf[0..in_buf_len]);
@@ -36,15 +36,16 @@ impl Aes {
             assert_eq!(in_ref_buf.remaining(), 0);
 
             let r = out_ref_buf.peek_read_buffer();
-            output.write(r.peek_remaining())?;
+            output.write_all(r.peek_remaining())?;
         }
 
+        in_buf.zeroize();
+        out_buf.zeroize();
         Ok(())
     }
 
     fn process(&mut self, input: &mut RefReadBuffer, output: &mut RefWriteBuffer, eof: bool) -> Result<BufferResult, SymmetricCipherError> {
         match self {
-            Aes::Encryptor(enc) => enc.encrypt(input, output, eof),
             Aes::Decryptor(dec) => dec.decrypt(input, output, eof)
         }
     }
@@ -2,7 +2,7 @@ use std::fs::File;
 use std::path::PathBuf;
 use chrono::{Duration, Utc};
 use serde::{Serialize, Deserialize};
-use crate::{AppError, exe_directory};
+use crate::{AppError, paths};
 use crate::backups::google_drive::token::Token;
 use crate::secure::Secure;
 
@@ -17,7 +17,14 @@ impl Credentials {
     pub fn load() -> Result<Self, AppError> {
         let path = Self::path()?;
         let file = File::open(path)?;
-        Ok(serde_json::from_reader(file)?)
+        let credentials: Credentials = serde_json::from_reader(file)?;
+
+        // Token stored by previous version is saved again with current backend
+        if credentials.token.as_ref().is_some_and(|t| t.is_outdated()) {
+            credentials.save()?;
+        }
+
+        Ok(credentials)
     }
 
     pub fn update(&mut self, token: Token) -> Result<(), AppError> {
@@ -47,7 +54,7 @@ impl Credentials {
     }
 
     fn path() -> Result<PathBuf, AppError> {
-        Ok(exe_directory()?.join("credentials.json"))
+        Ok(paths::config_directory()?.join("credentials.json"))
     }
 
     pub fn client_id(&self) -> &str {
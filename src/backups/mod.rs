@@ -3,7 +3,8 @@ use std::fs::File;
 use std::io::copy;
 use std::path::{Path, PathBuf};
 use zip::{ZipArchive, ZipWriter};
-use crate::{AppError, exe_directory, GoogleDrive};
+use crate::{AppError, export, GoogleDrive, paths};
+use crate::secret::Secret;
 
 pub mod google_drive;
 
@@ -25,63 +26,100 @@ impl Backup<GoogleDrive> {
 }
 
 impl <S: BackupStorage> Backup<S> {
-    pub fn backup<'a, 'b>(&mut self, names: impl Into<Names<'a, 'b>>, files: &[impl AsRef<Path>]) -> Result<(), AppError> {
+    /// Files and folders are stored by their path relative to root.
+    /// Archive is encrypted with passphrase, if it is given, before it is passed to storage
+    pub fn backup<'a, 'b>(&mut self, names: impl Into<Names<'a, 'b>>, root: &Path, files: &[impl AsRef<Path>], passphrase: Option<&Secret<String>>) -> Result<(), AppError> {
         let names = names.into();
-        let archive_file_path = self.temp_file_path()?;
+        let archive_file_path = self.temp_file_path("temp.zip")?;
         {
             let archive_file = File::create(&archive_file_path)?;
             let mut archive = ZipWriter::new(archive_file);
 
             for path in files {
                 let path = path.as_ref();
-                let file_name = Backup::<S>::extract_file_name(path)?;
-                archive.start_file(file_name, Default::default())?;
+                // Zip uses `/` separators on every platform
+                let file_name = paths::relative(root, path)?;
+                if path.is_dir() {
+                    archive.add_directory(file_name, Default::default())?;
+                    continue;
+                }
 
+                archive.start_file(file_name, Default::default())?;
                 let mut file = File::open(path)?;
                 copy(&mut file, &mut archive)?;
             }
         }
 
-        self.storage.save(names, &archive_file_path)?;
-        fs::remove_file(&archive_file_path)?;
+        match passphrase {
+            Some(passphrase) => {
+                let encrypted_file_path = self.temp_file_path("temp.zip.age")?;
+                let result = export::encrypt_file(&archive_file_path, &encrypted_file_path, passphrase)
+                    .and_then(|_| self.storage.save(names, &encrypted_file_path));
+                fs::remove_file(&archive_file_path)?;
+                let _ = fs::remove_file(&encrypted_file_path);
+                result?;
+            },
+            None => {
+                self.storage.save(names, &archive_file_path)?;
+                fs::remove_file(&archive_file_path)?;
+            }
+        }
 
         Ok(())
     }
 
-    pub fn restore<'a, 'b>(&mut self, names: impl Into<Names<'a, 'b>>, folder: impl AsRef<Path>) -> Result<(), AppError> {
+    /// Encrypted archive is not unpacked without passphrase, plain archives of old backups are unpacked as is
+    pub fn restore<'a, 'b>(&mut self, names: impl Into<Names<'a, 'b>>, folder: impl AsRef<Path>, passphrase: Option<&Secret<String>>) -> Result<(), AppError> {
         let names = names.into();
         let folder = folder.as_ref();
-        let archive_file_path = self.temp_file_path()?;
-        {
-            self.storage.restore(names, &archive_file_path)?;
-
-            let archive_file = File::open(&archive_file_path)?;
-            let mut archive = ZipArchive::new(archive_file)?;
+        let downloaded_file_path = self.temp_file_path("temp.download")?;
+        let archive_file_path = self.temp_file_path("temp.zip")?;
 
-            for i in 0..archive.len() {
-                let mut source_file = archive.by_index(i)?;
-                let destination_file_path = folder.join(source_file.name());
-                let mut destination_file = File::create(&destination_file_path)?;
-                copy(&mut source_file, &mut destination_file)?;
+        self.storage.restore(names, &downloaded_file_path)?;
+        let result = export::is_encrypted(&downloaded_file_path).and_then(|encrypted| {
+            if !encrypted {
+                return Backup::<S>::unpack(&downloaded_file_path, folder);
             }
-        }
 
-        fs::remove_file(&archive_file_path)?;
-        Ok(())
+            let passphrase = passphrase.ok_or(AppError::BackupPassphraseRequired)?;
+            export::decrypt_file(&downloaded_file_path, &archive_file_path, passphrase)?;
+            Backup::<S>::unpack(&archive_file_path, folder)
+        });
+
+        fs::remove_file(&downloaded_file_path)?;
+        let _ = fs::remove_file(&archive_file_path);
+        result
     }
 
-    fn extract_file_name(path: &Path) -> Result<&str, AppError> {
-        if let Some(os_file_name) = path.file_name() {
-            if let Some(file_name) = os_file_name.to_str() {
-                return Ok(file_name);
+    fn unpack(archive_file_path: &Path, folder: &Path) -> Result<(), AppError> {
+        let archive_file = File::open(archive_file_path)?;
+        let mut archive = ZipArchive::new(archive_file)?;
+
+        for i in 0..archive.len() {
+            let mut source_file = archive.by_index(i)?;
+            // Entries with absolute paths or ".." would be written outside of folder
+            let destination_file_path = match source_file.enclosed_name() {
+                Some(name) => folder.join(name),
+                None => return Err(AppError::internal("Invalid file name in backup"))
+            };
+
+            if source_file.is_dir() {
+                fs::create_dir_all(&destination_file_path)?;
+                continue;
             }
+
+            if let Some(parent) = destination_file_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut destination_file = File::create(&destination_file_path)?;
+            copy(&mut source_file, &mut destination_file)?;
         }
 
-        Err(AppError::Internal("It is not a file"))
+        Ok(())
     }
 
-    fn temp_file_path(&self) -> Result<PathBuf, AppError> {
-        Ok(exe_directory()?.join("temp.zip"))
+    fn temp_file_path(&self, name: &str) -> Result<PathBuf, AppError> {
+        Ok(paths::data_directory()?.join(name))
     }
 }
 
@@ -0,0 +1,3 @@
+fn main() {
+    notepad::cli::run()
+}
\ No newline at end of file
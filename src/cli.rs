@@ -0,0 +1,297 @@
+use std::{env, fs, process};
+use std::fs::File;
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use named_lock::NamedLock;
+use crate::error::AppError;
+use crate::{hash, paths};
+use crate::paths::Location;
+use crate::secret::Secret;
+use crate::state::app_state::AppState;
+use crate::state::config::Config;
+use crate::state::index::Index;
+use crate::state::key::{Factors, Key};
+use crate::state::tab::Tab;
+use crate::state::tab_content::TabContent;
+use crate::state::tabs::Tabs;
+use crate::state::vault::Vault;
+
+const USAGE : &str = "Usage: notepad-cli <command> [options]
+
+Commands:
+    list                         List notes in docs folder
+    print <note>                 Print note text
+    create <note> [--protect]    Create note with text from stdin
+    append <note>                Append text from stdin to note
+    encrypt <input> <output>     Encrypt text file
+    decrypt <input> [output]     Decrypt file, prints text when output is omitted
+    backup                       Upload docs to configured backup storage
+    restore                      Restore docs from configured backup storage
+
+Options:
+    --key-file <path>            Key file of protected note or file
+    --data-dir <path>            Data folder, NOTEPAD_DATA_DIR variable is used when omitted
+    --portable                   Keep data next to executable
+
+Password of protected note or file is read from the first line of stdin,
+text for create and append is read after it. Backup passphrase is read
+the same way by backup and restore.";
+
+struct Arguments {
+    command: String,
+    positional: Vec<String>,
+    key_file: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+    portable: bool,
+    protect: bool
+}
+
+impl Arguments {
+    fn parse(mut args: impl Iterator<Item = String>) -> Option<Self> {
+        let command = args.next()?;
+        let mut positional = Vec::new();
+        let mut key_file = None;
+        let mut data_dir = None;
+        let mut portable = false;
+        let mut protect = false;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--key-file" => key_file = Some(PathBuf::from(args.next()?)),
+                "--data-dir" => data_dir = Some(PathBuf::from(args.next()?)),
+                "--portable" => portable = true,
+                "--protect" => protect = true,
+                a if a.starts_with("--") => return None,
+                _ => positional.push(arg)
+            }
+        }
+
+        Some(Arguments {
+            command,
+            positional,
+            key_file,
+            data_dir,
+            portable,
+            protect
+        })
+    }
+
+    fn get(&self, index: usize) -> Option<&str> {
+        self.positional.get(index).map(|a| a.as_str())
+    }
+}
+
+/// Entry point of notepad-cli binary
+pub fn run() {
+    let args = match Arguments::parse(env::args().skip(1)) {
+        Some(a) => a,
+        None => usage()
+    };
+
+    if let Err(e) = execute(&args) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+fn execute(args: &Arguments) -> Result<(), AppError> {
+    // Files must not be changed behind the back of running app
+    let named_lock = NamedLock::create("notepad").map_err(|_| AppError::internal("Cannot create lock"))?;
+    let _guard = named_lock.try_lock().map_err(|_| AppError::AlreadyRunning)?;
+    paths::init(Location::resolve(args.data_dir.clone(), args.portable)?)?;
+
+    let mut input = io::stdin().lock();
+    match (args.command.as_str(), args.get(0), args.get(1)) {
+        ("list", None, None) => list(),
+        ("print", Some(note), None) => print(&mut input, note, args),
+        ("create", Some(note), None) => create(&mut input, note, args),
+        ("append", Some(note), None) => append(&mut input, note, args),
+        ("encrypt", Some(source), Some(destination)) => encrypt(&mut input, Path::new(source), Path::new(destination), args),
+        ("decrypt", Some(source), destination) => decrypt(&mut input, Path::new(source), destination.map(Path::new), args),
+        ("backup", None, None) => backup(&mut input),
+        ("restore", None, None) => restore(&mut input),
+        _ => usage()
+    }
+}
+
+fn list() -> Result<(), AppError> {
+    let tabs = Tabs::open()?;
+    for key in tabs.all_keys() {
+        let tab = tabs.get(key);
+        let kind = match (tab.clear(), tab.hidden_name()) {
+            (true, _) => "clear",
+            (false, false) => "protected",
+            (false, true) => "protected, hidden name"
+        };
+        let modified = tab.meta.modified.format("%Y-%m-%d %H:%M");
+        println!("{}\t{}\t{}", tab.file, kind, modified);
+    }
+    Ok(())
+}
+
+fn print(input: &mut impl BufRead, note: &str, args: &Arguments) -> Result<(), AppError> {
+    let tab = open(input, note, args)?;
+    let text = tab.text().ok_or(AppError::internal("File is not opened"))?;
+    io::stdout().write_all(text.as_bytes())?;
+    Ok(())
+}
+
+fn create(input: &mut impl BufRead, note: &str, args: &Arguments) -> Result<(), AppError> {
+    let key = if args.protect {
+        let config = Config::load()?;
+        let factors = factors(input, args)?;
+        config.check_password(factors.password.expose())?;
+        let params = config.kdf();
+        let mut key = Key::generate()?;
+        key.set_password(&factors, &params)?;
+        let code = key.new_recovery_code(&params)?;
+        eprintln!("Recovery code: {}", code.expose());
+        Some(key)
+    } else {
+        None
+    };
+
+    // Docs folder is created by the app on first run, data folder may be new
+    let docs = paths::docs_directory()?;
+    fs::create_dir_all(&docs)?;
+    let (folder, name) = split_note(note)?;
+    if !docs.join(folder).is_dir() {
+        return Err(AppError::FolderNotFound);
+    }
+    let mut tab = Tab::new(0, folder, name.to_string(), key, false)?;
+    tab.append(read_text(input)?.expose())?;
+    touch(&tab)?;
+    tab.lock()
+}
+
+fn append(input: &mut impl BufRead, note: &str, args: &Arguments) -> Result<(), AppError> {
+    let mut tab = open(input, note, args)?;
+    tab.append(read_text(input)?.expose())?;
+    touch(&tab)?;
+    tab.lock()
+}
+
+// Keeps modification time and tab order known to the app
+fn touch(tab: &Tab) -> Result<(), AppError> {
+    let mut index = Index::load()?;
+    index.touch(&tab.file);
+    index.save()
+}
+
+fn encrypt(input: &mut impl BufRead, source: &Path, destination: &Path, args: &Arguments) -> Result<(), AppError> {
+    if destination.exists() {
+        return Err(AppError::FileAlreadyExist);
+    }
+
+    let config = Config::load()?;
+    let factors = factors(input, args)?;
+    config.check_password(factors.password.expose())?;
+    let params = config.kdf();
+    let mut key = Key::generate()?;
+    key.set_password(&factors, &params)?;
+
+    let content = TabContent::Opened {
+        text: Secret::from_utf8(fs::read(source)?)?,
+        key,
+        title: None
+    };
+    content.save(&mut File::create(destination)?)?;
+    Ok(())
+}
+
+fn decrypt(input: &mut impl BufRead, source: &Path, destination: Option<&Path>, args: &Arguments) -> Result<(), AppError> {
+    if destination.is_some_and(|d| d.exists()) {
+        return Err(AppError::FileAlreadyExist);
+    }
+
+    let params = Config::load()?.kdf();
+    let content = TabContent::unlock(&mut File::open(source)?, &factors(input, args)?, &params, &Vault::load()?)?;
+    let text = match &content {
+        TabContent::Opened { text, .. } => text.expose(),
+        _ => return Err(AppError::internal("File is not opened"))
+    };
+
+    match destination {
+        Some(destination) => File::create(destination)?.write_all(text.as_bytes())?,
+        None => io::stdout().write_all(text.as_bytes())?
+    }
+    Ok(())
+}
+
+fn backup(input: &mut impl BufRead) -> Result<(), AppError> {
+    let mut state = AppState::open()?;
+    if state.config.encrypt_backup {
+        state.backup_passphrase.set(read_password(input)?.expose());
+    }
+    state.upload_backup()
+}
+
+fn restore(input: &mut impl BufRead) -> Result<(), AppError> {
+    let mut state = AppState::open()?;
+    state.backup_passphrase.set(read_password(input)?.expose());
+    state.download_backup()
+}
+
+// Every part of note path is checked as in the app, so path can't lead out of docs
+fn split_note(note: &str) -> Result<(&str, &str), AppError> {
+    let (folder, name) = note.rsplit_once('/').unwrap_or(("", note));
+    let parts = folder
+        .split('/')
+        .filter(|_| !folder.is_empty())
+        .chain([name]);
+    for part in parts {
+        if Tabs::check_name(part)? != part {
+            return Err(AppError::InvalidName);
+        }
+    }
+    Ok((folder, name))
+}
+
+fn open(input: &mut impl BufRead, note: &str, args: &Arguments) -> Result<Tab, AppError> {
+    split_note(note)?;
+    let mut tab = Tab::load(0, note)?;
+    if tab.opened() {
+        return Ok(tab);
+    }
+
+    let params = Config::load()?.kdf();
+    let factors = factors(input, args)?;
+
+    // Master password unlocks tabs stored in vault, same as in the app
+    let mut vault = Vault::load()?;
+    if tab.in_vault() && vault.exists() {
+        let _ = vault.unlock(factors.password.expose());
+    }
+
+    tab.open_with(&factors, &params, &vault)?;
+    Ok(tab)
+}
+
+fn factors(input: &mut impl BufRead, args: &Arguments) -> Result<Factors, AppError> {
+    let key_file = match &args.key_file {
+        Some(path) => Some(hash::key_file(path)?),
+        None => None
+    };
+    Ok(Factors::new(read_password(input)?, key_file))
+}
+
+fn read_password(input: &mut impl BufRead) -> Result<Secret<String>, AppError> {
+    let mut password = Secret::new(String::with_capacity(256));
+    password.edit(|p| input.read_line(p))?;
+    password.edit(|p| {
+        let len = p.trim_end_matches(&['\r', '\n'][..]).len();
+        p.truncate(len);
+    });
+    Ok(password)
+}
+
+fn read_text(input: &mut impl BufRead) -> Result<Secret<String>, AppError> {
+    let mut text = Secret::empty();
+    text.edit(|t| input.read_to_string(t))?;
+    Ok(text)
+}
\ No newline at end of file
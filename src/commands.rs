@@ -1,4 +1,46 @@
-use druid::Selector;
+use druid::{FileInfo, Selector};
+use crate::secret::Secret;
+use crate::state::index::LabelColor;
+use crate::windows::generator_window::Output;
 
 pub const SHOW_BACKUPS: Selector<()> = Selector::new("notepad.show_backups");
-pub const SHOW_MAIN_WINDOW: Selector<()> = Selector::new("notepad.show_main_window");
\ No newline at end of file
+pub const SHOW_MAIN_WINDOW: Selector<()> = Selector::new("notepad.show_main_window");
+pub const OPEN_TAB: Selector<u64> = Selector::new("notepad.open_tab");
+pub const SHOW_CHANGE_PASSWORD: Selector<u64> = Selector::new("notepad.show_change_password");
+pub const SHOW_PROTECT: Selector<u64> = Selector::new("notepad.show_protect");
+pub const SHOW_REMOVE_PROTECTION: Selector<u64> = Selector::new("notepad.show_remove_protection");
+pub const SHOW_SETTINGS: Selector<()> = Selector::new("notepad.show_settings");
+pub const SHOW_TRASH: Selector<()> = Selector::new("notepad.show_trash");
+pub const LOCK_TAB: Selector<u64> = Selector::new("notepad.lock_tab");
+pub const LOCK_ALL: Selector<()> = Selector::new("notepad.lock_all");
+pub const SHOW_CREATE_VAULT: Selector<()> = Selector::new("notepad.show_create_vault");
+pub const SHOW_UNLOCK_VAULT: Selector<()> = Selector::new("notepad.show_unlock_vault");
+pub const SHOW_CHANGE_VAULT_PASSWORD: Selector<()> = Selector::new("notepad.show_change_vault_password");
+pub const ADD_TO_VAULT: Selector<u64> = Selector::new("notepad.add_to_vault");
+pub const NEW_RECOVERY_CODE: Selector<u64> = Selector::new("notepad.new_recovery_code");
+pub const HIDE_NAME: Selector<u64> = Selector::new("notepad.hide_name");
+pub const SHOW_NAME: Selector<u64> = Selector::new("notepad.show_name");
+pub const SHOW_EXPORT: Selector<u64> = Selector::new("notepad.show_export");
+pub const SHOW_IMPORT: Selector<()> = Selector::new("notepad.show_import");
+pub const MOVE_TAB_LEFT: Selector<u64> = Selector::new("notepad.move_tab_left");
+pub const MOVE_TAB_RIGHT: Selector<u64> = Selector::new("notepad.move_tab_right");
+pub const PIN_TAB: Selector<u64> = Selector::new("notepad.pin_tab");
+pub const UNPIN_TAB: Selector<u64> = Selector::new("notepad.unpin_tab");
+pub const SET_TAB_COLOR: Selector<(u64, Option<LabelColor>)> = Selector::new("notepad.set_tab_color");
+pub const SELECT_TAB: Selector<u64> = Selector::new("notepad.select_tab");
+pub const SHOW_RENAME_TAB: Selector<u64> = Selector::new("notepad.show_rename_tab");
+pub const SHOW_HISTORY: Selector<u64> = Selector::new("notepad.show_history");
+pub const SHOW_MOVE_TO_FOLDER: Selector<u64> = Selector::new("notepad.show_move_to_folder");
+pub const SHOW_NEW_FOLDER: Selector<String> = Selector::new("notepad.show_new_folder");
+pub const REMOVE_FOLDER: Selector<String> = Selector::new("notepad.remove_folder");
+pub const AUTOSAVE: Selector<()> = Selector::new("notepad.autosave");
+pub const WRITE_JOURNAL: Selector<()> = Selector::new("notepad.write_journal");
+pub const JOURNAL_ERROR: Selector<String> = Selector::new("notepad.journal_error");
+pub const SHOW_RECOVERY: Selector<()> = Selector::new("notepad.show_recovery");
+pub const TEXT_CHANGED: Selector<u64> = Selector::new("notepad.text_changed");
+pub const SHOW_GENERATOR: Selector<Output> = Selector::new("notepad.show_generator");
+pub const KEY_FILE_SELECTED: Selector<FileInfo> = Selector::new("notepad.key_file_selected");
+pub const KEY_FILE_CANCELLED: Selector<()> = Selector::new("notepad.key_file_cancelled");
+pub const EXPORT_FILE_SELECTED: Selector<FileInfo> = Selector::new("notepad.export_file_selected");
+pub const IMPORT_FILE_SELECTED: Selector<FileInfo> = Selector::new("notepad.import_file_selected");
+pub const INSERT_TEXT: Selector<Secret<String>> = Selector::new("notepad.insert_text");
\ No newline at end of file
@@ -1,5 +1,6 @@
 use druid::{AppDelegate, Command, commands, DelegateCtx, Env, Handled, Target, WindowHandle, WindowId};
 use crate::{AppState, windows};
+use crate::error::AppError;
 
 pub struct Delegate {
     main: Option<Window>
@@ -35,9 +36,37 @@ impl AppDelegate<AppState> for Delegate {
             c if c.is(commands::SAVE_FILE) => {
                 if let Err(e) = state.save() {
                     ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                } else if state.config.auto_backup && state.backup_passphrase_missing() {
+                    ctx.new_window(windows::information_window::new("Notes are saved, but backup is skipped: enter backup passphrase in Backups"));
                 }
                 Handled::Yes
             },
+            c if c.is(crate::commands::AUTOSAVE) => {
+                if let Err(e) = state.autosave() {
+                    ctx.new_window(windows::information_window::new(format!("Autosave error: {}", e)));
+                }
+                Handled::Yes
+            },
+            c if c.is(crate::commands::WRITE_JOURNAL) => {
+                let sink = ctx.get_external_handle();
+                state.tabs.write_journal_in_background(move |result| {
+                    if let Err(e) = result {
+                        if let Err(e) = sink.submit_command(crate::commands::JOURNAL_ERROR, e.to_string(), Target::Auto) {
+                            println!("Cannot submit command:{}", e)
+                        }
+                    }
+                });
+                Handled::Yes
+            },
+            c if c.is(crate::commands::JOURNAL_ERROR) => {
+                let error = c.get_unchecked(crate::commands::JOURNAL_ERROR);
+                ctx.new_window(windows::information_window::new(format!("Recovery journal error: {}", error)));
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SHOW_RECOVERY) => {
+                ctx.new_window(windows::recovery_window::new());
+                Handled::Yes
+            },
             c if c.is(crate::commands::SHOW_BACKUPS) => {
                 ctx.new_window(windows::backup_window::new());
                 Handled::Yes
@@ -50,6 +79,189 @@ impl AppDelegate<AppState> for Delegate {
                     Handled::No
                 }
             },
+            c if c.is(crate::commands::OPEN_TAB) => {
+                let key = *c.get_unchecked(crate::commands::OPEN_TAB);
+                match state.open_tab(key) {
+                    Ok(_) => {},
+                    Err(AppError::InvalidPassword) => {
+                        ctx.new_window(windows::information_window::new("Invalid password"));
+                    },
+                    Err(e) => {
+                        ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                    }
+                }
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SHOW_CHANGE_PASSWORD) => {
+                let key = *c.get_unchecked(crate::commands::SHOW_CHANGE_PASSWORD);
+                ctx.new_window(windows::change_password_window::new(key));
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SHOW_PROTECT) => {
+                let key = *c.get_unchecked(crate::commands::SHOW_PROTECT);
+                ctx.new_window(windows::protect_window::new(key));
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SHOW_REMOVE_PROTECTION) => {
+                let key = *c.get_unchecked(crate::commands::SHOW_REMOVE_PROTECTION);
+                ctx.new_window(windows::remove_protection_window::new(key));
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SHOW_SETTINGS) => {
+                ctx.new_window(windows::settings_window::new());
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SHOW_TRASH) => {
+                ctx.new_window(windows::trash_window::new());
+                Handled::Yes
+            },
+            c if c.is(crate::commands::LOCK_TAB) => {
+                let key = *c.get_unchecked(crate::commands::LOCK_TAB);
+                if let Err(e) = state.lock_tab(key) {
+                    ctx.new_window(windows::information_window::new(format!("Cannot lock: {}", e)));
+                }
+                Handled::Yes
+            },
+            c if c.is(crate::commands::LOCK_ALL) => {
+                if let Err(e) = state.lock_all() {
+                    ctx.new_window(windows::information_window::new(format!("Cannot lock: {}", e)));
+                }
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SHOW_EXPORT) => {
+                let key = *c.get_unchecked(crate::commands::SHOW_EXPORT);
+                ctx.new_window(windows::export_window::new(key));
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SHOW_IMPORT) => {
+                ctx.new_window(windows::import_window::new());
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SHOW_GENERATOR) => {
+                let output = *c.get_unchecked(crate::commands::SHOW_GENERATOR);
+                match state.generator.generate() {
+                    Ok(_) => ctx.new_window(windows::generator_window::new(output)),
+                    Err(e) => ctx.new_window(windows::information_window::new(format!("Error: {}", e)))
+                }
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SHOW_CREATE_VAULT) => {
+                ctx.new_window(windows::create_vault_window::new());
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SHOW_UNLOCK_VAULT) => {
+                ctx.new_window(windows::unlock_vault_window::new());
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SHOW_CHANGE_VAULT_PASSWORD) => {
+                ctx.new_window(windows::change_vault_password_window::new());
+                Handled::Yes
+            },
+            c if c.is(crate::commands::ADD_TO_VAULT) => {
+                let key = *c.get_unchecked(crate::commands::ADD_TO_VAULT);
+                if let Err(e) = state.add_tab_to_vault(key) {
+                    ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                }
+                Handled::Yes
+            },
+            c if c.is(crate::commands::HIDE_NAME) => {
+                let key = *c.get_unchecked(crate::commands::HIDE_NAME);
+                if let Err(e) = state.hide_tab_name(key) {
+                    ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                }
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SHOW_NAME) => {
+                let key = *c.get_unchecked(crate::commands::SHOW_NAME);
+                if let Err(e) = state.show_tab_name(key) {
+                    ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                }
+                Handled::Yes
+            },
+            c if c.is(crate::commands::MOVE_TAB_LEFT) => {
+                let key = *c.get_unchecked(crate::commands::MOVE_TAB_LEFT);
+                if let Err(e) = state.tabs.move_tab(key, false) {
+                    ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                }
+                Handled::Yes
+            },
+            c if c.is(crate::commands::MOVE_TAB_RIGHT) => {
+                let key = *c.get_unchecked(crate::commands::MOVE_TAB_RIGHT);
+                if let Err(e) = state.tabs.move_tab(key, true) {
+                    ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                }
+                Handled::Yes
+            },
+            c if c.is(crate::commands::PIN_TAB) => {
+                let key = *c.get_unchecked(crate::commands::PIN_TAB);
+                if let Err(e) = state.tabs.set_pinned(key, true) {
+                    ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                }
+                Handled::Yes
+            },
+            c if c.is(crate::commands::UNPIN_TAB) => {
+                let key = *c.get_unchecked(crate::commands::UNPIN_TAB);
+                if let Err(e) = state.tabs.set_pinned(key, false) {
+                    ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                }
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SET_TAB_COLOR) => {
+                let (key, color) = *c.get_unchecked(crate::commands::SET_TAB_COLOR);
+                if let Err(e) = state.tabs.set_color(key, color) {
+                    ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                }
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SHOW_RENAME_TAB) => {
+                let key = *c.get_unchecked(crate::commands::SHOW_RENAME_TAB);
+                let tab = state.tabs.get(key);
+                if tab.hidden_name() && !tab.unlocked() {
+                    ctx.new_window(windows::information_window::new("Unlock note to rename it"));
+                } else {
+                    state.new_name = tab.name.clone();
+                    ctx.new_window(windows::rename_tab_window::new(key));
+                }
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SHOW_HISTORY) => {
+                let key = *c.get_unchecked(crate::commands::SHOW_HISTORY);
+                match state.load_versions(key) {
+                    Ok(_) => ctx.new_window(windows::history_window::new(key)),
+                    Err(e) => ctx.new_window(windows::information_window::new(format!("Error: {}", e)))
+                }
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SHOW_MOVE_TO_FOLDER) => {
+                let key = *c.get_unchecked(crate::commands::SHOW_MOVE_TO_FOLDER);
+                ctx.new_window(windows::move_to_folder_window::new(key));
+                Handled::Yes
+            },
+            c if c.is(crate::commands::SHOW_NEW_FOLDER) => {
+                let parent = c.get_unchecked(crate::commands::SHOW_NEW_FOLDER).clone();
+                ctx.new_window(windows::new_folder_window::new(parent));
+                Handled::Yes
+            },
+            c if c.is(crate::commands::REMOVE_FOLDER) => {
+                let folder = c.get_unchecked(crate::commands::REMOVE_FOLDER);
+                if let Err(e) = state.tabs.remove_folder(folder) {
+                    ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                }
+                Handled::Yes
+            },
+            c if c.is(crate::commands::TEXT_CHANGED) => {
+                let key = *c.get_unchecked(crate::commands::TEXT_CHANGED);
+                state.tabs.touch(key);
+                Handled::Yes
+            },
+            c if c.is(crate::commands::NEW_RECOVERY_CODE) => {
+                let key = *c.get_unchecked(crate::commands::NEW_RECOVERY_CODE);
+                match state.new_recovery_code(key) {
+                    Ok(code) => ctx.new_window(windows::recovery_code_window::new(&code)),
+                    Err(e) => ctx.new_window(windows::information_window::new(format!("Error: {}", e)))
+                }
+                Handled::Yes
+            },
             c if c.is(commands::SHOW_ABOUT) => {
                 ctx.new_window(windows::about_window::new());
                 Handled::Yes
@@ -64,15 +276,24 @@ impl AppDelegate<AppState> for Delegate {
         &mut self,
         id: WindowId,
         handle: WindowHandle,
-        _data: &mut AppState,
+        data: &mut AppState,
         _env: &Env,
-        _ctx: &mut DelegateCtx) {
+        ctx: &mut DelegateCtx) {
 
         if self.main.is_none() {
             self.main = Some(Window {
                 id,
                 handle: handle.clone()
             });
+
+            // One prompt at startup opens all vault tabs
+            if data.vault.exists() && !data.vault.unlocked() {
+                ctx.new_window(windows::unlock_vault_window::new());
+            }
+
+            if !data.recovery.is_empty() {
+                ctx.new_window(windows::recovery_window::new());
+            }
         }
     }
 
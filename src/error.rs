@@ -1,4 +1,5 @@
 use std::io;
+use std::str::Utf8Error;
 use std::string::FromUtf8Error;
 use serde_json;
 use getrandom;
@@ -14,22 +15,59 @@ pub enum AppError {
     FileAlreadyExist,
     #[error("Invalid password")]
     InvalidPassword,
+    #[error("File is damaged")]
+    Damaged,
+    #[error("Key file is missing or does not match")]
+    KeyFileRequired,
+    #[error("Vault is locked")]
+    VaultLocked,
+    #[error("Passwords do not match")]
+    PasswordsMismatch,
+    #[error("Password is too weak")]
+    WeakPassword,
+    #[error("Password is empty")]
+    EmptyPassword,
+    #[error("Key derivation parameters are out of supported range")]
+    UnsupportedKdf,
+    #[error("Unsupported file format: unknown {0} {1}")]
+    UnsupportedFormat(&'static str, u8),
+    #[error("File is not encrypted with a passphrase")]
+    NoPassphrase,
+    #[error("Notepad is already running, close it first")]
+    AlreadyRunning,
+    #[error("Invalid name")]
+    InvalidName,
+    #[error("Folder is not empty")]
+    FolderNotEmpty,
+    #[error("Folder not found")]
+    FolderNotFound,
+    #[error("Backup passphrase is required")]
+    BackupPassphraseRequired,
     #[error("Backup file not found")]
     BackupNotFound,
     #[error("Io error: {0}")]
     Io(#[from] io::Error),
     #[error("Cypher error")]
     Cypher(SymmetricCipherError),
+    #[error("Key derivation error: {0}")]
+    Kdf(argon2::Error),
     #[error("Random error: {0}")]
     Random(#[from] getrandom::Error),
     #[error("FromUtf8 error: {0}")]
     FromUtf8(#[from] FromUtf8Error),
+    #[error("Utf8 error: {0}")]
+    Utf8(#[from] Utf8Error),
     #[error("Serde error: {0}")]
     Serde(#[from] serde_json::Error),
     #[error("Request error: {0}")]
     Request(#[from] reqwest::Error),
     #[error("Zip error: {0}")]
     Zip(#[from] ZipError),
+    #[error("Age error: {0}")]
+    Age(age::DecryptError),
+    #[cfg(not(windows))]
+    #[error("Secret service error: {0}")]
+    SecretService(dbus_secret_service::Error),
     #[error("Google drive request client error {0}")]
     GoogleDriveClientError(u16)
 }
@@ -44,4 +82,17 @@ impl From<SymmetricCipherError> for AppError {
     fn from(e: SymmetricCipherError) -> Self {
         AppError::Cypher(e)
     }
+}
+
+impl From<argon2::Error> for AppError {
+    fn from(e: argon2::Error) -> Self {
+        AppError::Kdf(e)
+    }
+}
+
+#[cfg(not(windows))]
+impl From<dbus_secret_service::Error> for AppError {
+    fn from(e: dbus_secret_service::Error) -> Self {
+        AppError::SecretService(e)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,87 @@
+use std::fs::File;
+use std::io::{copy, Read, Write};
+use std::path::Path;
+use age::{DecryptError, Decryptor, Encryptor};
+use age::secrecy;
+use zeroize::Zeroize;
+use crate::error::AppError;
+use crate::secret::{self, Secret};
+
+pub const EXTENSION : &str = "age";
+const MAGIC : &[u8] = b"age-encryption.org/";
+
+/// Writes text as age file encrypted with passphrase (scrypt recipient),
+/// so it can be opened with `age -d` or `rage -d` without the app.
+pub fn write(path: &Path, passphrase: Secret<String>, text: &[u8]) -> Result<(), AppError> {
+    let encryptor = Encryptor::with_user_passphrase(secrecy::Secret::new(passphrase.expose().clone()));
+
+    let file = File::create(path)?;
+    let mut writer = encryptor.wrap_output(file)?;
+    writer.write_all(text)?;
+    writer.finish()?.flush()?;
+    Ok(())
+}
+
+/// Reads passphrase encrypted age file, files encrypted to public keys are not supported
+pub fn read(path: &Path, passphrase: Secret<String>) -> Result<Secret<String>, AppError> {
+    let file = File::open(path)?;
+    let mut text_bytes = secret::text_buffer(&file)?;
+
+    let decryptor = match Decryptor::new(file).map_err(from_decrypt_error)? {
+        Decryptor::Passphrase(d) => d,
+        _ => return Err(AppError::NoPassphrase)
+    };
+
+    let mut reader = decryptor
+        .decrypt(&secrecy::Secret::new(passphrase.expose().clone()), None)
+        .map_err(from_decrypt_error)?;
+
+    if let Err(e) = reader.read_to_end(&mut text_bytes) {
+        text_bytes.zeroize();
+        return Err(AppError::from(e));
+    }
+
+    Secret::from_utf8(text_bytes)
+}
+
+fn from_decrypt_error(e: DecryptError) -> AppError {
+    match e {
+        DecryptError::DecryptionFailed | DecryptError::KeyDecryptionFailed => AppError::InvalidPassword,
+        e => AppError::Age(e)
+    }
+}
+
+/// Encrypts whole file with passphrase, used for files too big to be kept in memory
+pub fn encrypt_file(source: &Path, destination: &Path, passphrase: &Secret<String>) -> Result<(), AppError> {
+    let encryptor = Encryptor::with_user_passphrase(secrecy::Secret::new(passphrase.expose().clone()));
+
+    let mut input = File::open(source)?;
+    let mut writer = encryptor.wrap_output(File::create(destination)?)?;
+    copy(&mut input, &mut writer)?;
+    writer.finish()?.flush()?;
+    Ok(())
+}
+
+pub fn decrypt_file(source: &Path, destination: &Path, passphrase: &Secret<String>) -> Result<(), AppError> {
+    let decryptor = match Decryptor::new(File::open(source)?).map_err(from_decrypt_error)? {
+        Decryptor::Passphrase(d) => d,
+        _ => return Err(AppError::NoPassphrase)
+    };
+
+    let mut reader = decryptor
+        .decrypt(&secrecy::Secret::new(passphrase.expose().clone()), None)
+        .map_err(from_decrypt_error)?;
+
+    copy(&mut reader, &mut File::create(destination)?)?;
+    Ok(())
+}
+
+pub fn is_encrypted(path: &Path) -> Result<bool, AppError> {
+    let mut magic = [0u8; MAGIC.len()];
+    let mut file = File::open(path)?;
+    match file.read_exact(&mut magic) {
+        Ok(_) => Ok(magic == MAGIC),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(AppError::from(e))
+    }
+}
\ No newline at end of file
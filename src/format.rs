@@ -0,0 +1,543 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use crate::error::AppError;
+use zeroize::Zeroize;
+use crate::{gcm, gen, hash};
+use crate::gcm::Gcm;
+use crate::hash::Argon2Params;
+use crate::secret::Secret;
+
+pub const LEGACY_MAGIC : [u8; 3] = [b'E', b'N', b'C'];
+// First byte is not valid UTF-8, so clear text can't be mistaken for an encrypted file
+pub const MAGIC : [u8; 4] = [0x89, b'N', b'P', b'E'];
+pub const VAULT_MAGIC : [u8; 4] = [0x89, b'N', b'P', b'V'];
+pub const VERSION : u8 = 2;
+pub const VAULT_VERSION : u8 = 1;
+pub const VAULT_ID_SIZE : usize = 16;
+
+const SALT_SIZE : usize = 16;
+// Nonce | encrypted 256 bit key | tag
+const WRAPPED_KEY_SIZE : usize = gcm::NONCE_SIZE + 32 + 16;
+
+// Version 1 header flags and slot flags
+const KEY_FILE_FLAG : u8 = 0x01;
+const KNOWN_FLAGS : u8 = KEY_FILE_FLAG;
+// Version 2 header flags, encrypted data starts with the title
+const TITLE_FLAG : u8 = 0x01;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Clear,
+    Legacy,
+    Versioned
+}
+
+impl Format {
+    pub fn detect(file: &mut File) -> Result<Self, AppError> {
+        let mut magic = Vec::with_capacity(MAGIC.len());
+        file.seek(SeekFrom::Start(0))?;
+        file.take(MAGIC.len() as u64).read_to_end(&mut magic)?;
+        file.seek(SeekFrom::Start(0))?;
+
+        Ok(if magic == MAGIC {
+            Format::Versioned
+        } else if magic.starts_with(&LEGACY_MAGIC) {
+            Format::Legacy
+        } else {
+            Format::Clear
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cipher {
+    Aes256Gcm,
+    /// Chunked AES-256-GCM, see `stream`
+    Aes256GcmStream
+}
+
+impl Cipher {
+    fn id(&self) -> u8 {
+        match self {
+            Cipher::Aes256Gcm => 1,
+            Cipher::Aes256GcmStream => 2
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self, AppError> {
+        match id {
+            1 => Ok(Cipher::Aes256Gcm),
+            2 => Ok(Cipher::Aes256GcmStream),
+            _ => Err(AppError::UnsupportedFormat("cipher", id))
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Kdf {
+    Pbkdf2Sha3 {
+        iterations: u32,
+        salt: [u8; SALT_SIZE]
+    },
+    Argon2id {
+        params: Argon2Params,
+        salt: [u8; SALT_SIZE]
+    }
+}
+
+impl Kdf {
+    pub fn argon2id(params: Argon2Params) -> Result<Self, AppError> {
+        let mut salt = [0u8; SALT_SIZE];
+        getrandom::getrandom(&mut salt)?;
+
+        Ok(Kdf::Argon2id {
+            params,
+            salt
+        })
+    }
+
+    pub fn derive(&self, password: &str, key_file: Option<&[u8]>) -> Result<Secret<[u8; 32]>, AppError> {
+        match self {
+            Kdf::Pbkdf2Sha3 { .. } if key_file.is_some() => Err(AppError::internal("Key file is not supported by kdf")),
+            Kdf::Pbkdf2Sha3 { iterations, salt } => Ok(hash::pbkdf2(password, salt, *iterations)),
+            Kdf::Argon2id { params, salt } => hash::argon2id(password, key_file, salt, params)
+        }
+    }
+
+    fn id(&self) -> u8 {
+        match self {
+            Kdf::Pbkdf2Sha3 { .. } => 1,
+            Kdf::Argon2id { .. } => 2
+        }
+    }
+
+    fn write<W: Write>(&self, w: &mut W) -> Result<(), AppError> {
+        w.write_u8(self.id())?;
+        match self {
+            Kdf::Pbkdf2Sha3 { iterations, salt } => {
+                w.write_u32::<LittleEndian>(*iterations)?;
+                w.write_all(salt)?;
+            },
+            Kdf::Argon2id { params, salt } => {
+                w.write_u32::<LittleEndian>(params.memory)?;
+                w.write_u32::<LittleEndian>(params.iterations)?;
+                w.write_u32::<LittleEndian>(params.parallelism)?;
+                w.write_all(salt)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read<R: Read>(r: &mut R) -> Result<Self, AppError> {
+        let id = r.read_u8()?;
+        match id {
+            1 => {
+                let iterations = r.read_u32::<LittleEndian>()?;
+                if iterations == 0 {
+                    return Err(AppError::UnsupportedFormat("kdf parameters", id));
+                }
+                let mut salt = [0u8; SALT_SIZE];
+                r.read_exact(&mut salt)?;
+                Ok(Kdf::Pbkdf2Sha3 {
+                    iterations,
+                    salt
+                })
+            },
+            2 => {
+                let params = Argon2Params {
+                    memory: r.read_u32::<LittleEndian>()?,
+                    iterations: r.read_u32::<LittleEndian>()?,
+                    parallelism: r.read_u32::<LittleEndian>()?
+                };
+                if !params.is_supported() {
+                    return Err(AppError::UnsupportedFormat("kdf parameters", id));
+                }
+                let mut salt = [0u8; SALT_SIZE];
+                r.read_exact(&mut salt)?;
+                Ok(Kdf::Argon2id {
+                    params,
+                    salt
+                })
+            },
+            _ => Err(AppError::UnsupportedFormat("kdf", id))
+        }
+    }
+}
+
+/// Random key encrypted with another key (password, vault master key)
+#[derive(Clone, Debug, PartialEq)]
+pub struct WrappedKey {
+    bytes: [u8; WRAPPED_KEY_SIZE]
+}
+
+impl WrappedKey {
+    pub fn wrap(kek: &[u8], key: &Secret<[u8; 32]>) -> Result<Self, AppError> {
+        let nonce = gen::bytes(gcm::NONCE_SIZE)?;
+        let encrypted = Gcm::new(kek).encrypt(&nonce, &[], key.expose())?;
+
+        let mut bytes = [0u8; WRAPPED_KEY_SIZE];
+        bytes[..gcm::NONCE_SIZE].copy_from_slice(&nonce);
+        bytes[gcm::NONCE_SIZE..].copy_from_slice(&encrypted);
+        Ok(WrappedKey {
+            bytes
+        })
+    }
+
+    pub fn unwrap(&self, kek: &[u8]) -> Result<Secret<[u8; 32]>, AppError> {
+        let (nonce, encrypted) = self.bytes.split_at(gcm::NONCE_SIZE);
+        let mut decrypted = Gcm::new(kek).decrypt(nonce, &[], encrypted)?;
+
+        let mut key = Secret::zeroed();
+        key.edit(|key| key.copy_from_slice(&decrypted));
+        decrypted.zeroize();
+        Ok(key)
+    }
+
+    fn write<W: Write>(&self, w: &mut W) -> Result<(), AppError> {
+        w.write_all(&self.bytes)?;
+        Ok(())
+    }
+
+    fn read<R: Read>(r: &mut R) -> Result<Self, AppError> {
+        let mut bytes = [0u8; WRAPPED_KEY_SIZE];
+        r.read_exact(&mut bytes)?;
+        Ok(WrappedKey {
+            bytes
+        })
+    }
+}
+
+/// Way to get data key of a file
+#[derive(Clone, Debug, PartialEq)]
+pub enum Slot {
+    /// Version 1 files, data key is derived from password itself
+    Direct {
+        kdf: Kdf,
+        key_file: bool
+    },
+    Password {
+        kdf: Kdf,
+        key_file: bool,
+        wrapped: WrappedKey
+    },
+    Vault {
+        id: [u8; VAULT_ID_SIZE],
+        wrapped: WrappedKey
+    },
+    Recovery {
+        kdf: Kdf,
+        wrapped: WrappedKey
+    }
+}
+
+impl Slot {
+    pub fn requires_key_file(&self) -> bool {
+        match self {
+            Slot::Direct { key_file, .. } => *key_file,
+            Slot::Password { key_file, .. } => *key_file,
+            Slot::Vault { .. } => false,
+            Slot::Recovery { .. } => false
+        }
+    }
+
+    fn write<W: Write>(&self, w: &mut W) -> Result<(), AppError> {
+        match self {
+            Slot::Direct { .. } => return Err(AppError::internal("Direct slot cannot be written")),
+            Slot::Password { kdf, key_file, wrapped } => {
+                w.write_u8(1)?;
+                w.write_u8(if *key_file { KEY_FILE_FLAG } else { 0 })?;
+                kdf.write(w)?;
+                wrapped.write(w)?;
+            },
+            Slot::Vault { id, wrapped } => {
+                w.write_u8(2)?;
+                w.write_all(id)?;
+                wrapped.write(w)?;
+            },
+            Slot::Recovery { kdf, wrapped } => {
+                w.write_u8(3)?;
+                kdf.write(w)?;
+                wrapped.write(w)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read<R: Read>(r: &mut R) -> Result<Self, AppError> {
+        let id = r.read_u8()?;
+        match id {
+            1 => {
+                let flags = r.read_u8()?;
+                if flags & !KNOWN_FLAGS != 0 {
+                    return Err(AppError::UnsupportedFormat("slot flags", flags));
+                }
+                Ok(Slot::Password {
+                    key_file: flags & KEY_FILE_FLAG != 0,
+                    kdf: Kdf::read(r)?,
+                    wrapped: WrappedKey::read(r)?
+                })
+            },
+            2 => {
+                let mut id = [0u8; VAULT_ID_SIZE];
+                r.read_exact(&mut id)?;
+                Ok(Slot::Vault {
+                    id,
+                    wrapped: WrappedKey::read(r)?
+                })
+            },
+            3 => {
+                Ok(Slot::Recovery {
+                    kdf: Kdf::read(r)?,
+                    wrapped: WrappedKey::read(r)?
+                })
+            },
+            _ => Err(AppError::UnsupportedFormat("slot", id))
+        }
+    }
+}
+
+/// Header of versioned encrypted file.
+/// Version 1: magic | version | cipher | flags | kdf id | kdf params | nonce
+/// Version 2: magic | version | cipher | flags | slots count | slots | nonce
+/// Whole header is authenticated as associated data of the cipher.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Header {
+    pub cipher: Cipher,
+    pub slots: Vec<Slot>,
+    // Title length (u16) and title precede the text, used when file name is hidden
+    pub title: bool,
+    pub nonce: [u8; gcm::NONCE_SIZE]
+}
+
+impl Header {
+    pub fn new(slots: Vec<Slot>, title: bool) -> Result<Self, AppError> {
+        let mut nonce = [0u8; gcm::NONCE_SIZE];
+        nonce.copy_from_slice(&gen::bytes(gcm::NONCE_SIZE)?);
+
+        Ok(Header {
+            cipher: Cipher::Aes256GcmStream,
+            slots,
+            title,
+            nonce
+        })
+    }
+
+    pub fn requires_key_file(&self) -> bool {
+        self.slots.iter().any(|s| s.requires_key_file())
+    }
+
+    pub fn in_vault(&self) -> bool {
+        self.slots.iter().any(|s| matches!(s, Slot::Vault { .. }))
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, AppError> {
+        let mut bytes = Vec::new();
+        bytes.write_all(&MAGIC)?;
+        bytes.write_u8(VERSION)?;
+        bytes.write_u8(self.cipher.id())?;
+        bytes.write_u8(if self.title { TITLE_FLAG } else { 0 })?;
+        bytes.write_u8(self.slots.len() as u8)?;
+        for slot in &self.slots {
+            slot.write(&mut bytes)?;
+        }
+        bytes.write_all(&self.nonce)?;
+        Ok(bytes)
+    }
+
+    /// Reads header and returns it with its raw bytes
+    pub fn read<R: Read>(r: &mut R) -> Result<(Self, Vec<u8>), AppError> {
+        let mut r = Recorder::new(r);
+
+        let mut magic = [0u8; MAGIC.len()];
+        r.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(AppError::internal("File is not encrypted"));
+        }
+
+        let version = r.read_u8()?;
+        if version != 1 && version != VERSION {
+            return Err(AppError::UnsupportedFormat("version", version));
+        }
+
+        let cipher = Cipher::from_id(r.read_u8()?)?;
+        let flags = r.read_u8()?;
+        let title = version != 1 && flags & TITLE_FLAG != 0;
+        let slots = if version == 1 {
+            if flags & !KNOWN_FLAGS != 0 {
+                return Err(AppError::UnsupportedFormat("flags", flags));
+            }
+            vec![Slot::Direct {
+                kdf: Kdf::read(&mut r)?,
+                key_file: flags & KEY_FILE_FLAG != 0
+            }]
+        } else {
+            if flags & !TITLE_FLAG != 0 {
+                return Err(AppError::UnsupportedFormat("flags", flags));
+            }
+            let count = r.read_u8()?;
+            let mut slots = Vec::with_capacity(count as usize);
+            for _ in 0..count {
+                slots.push(Slot::read(&mut r)?);
+            }
+            slots
+        };
+
+        let mut nonce = [0u8; gcm::NONCE_SIZE];
+        r.read_exact(&mut nonce)?;
+
+        let header = Header {
+            cipher,
+            slots,
+            title,
+            nonce
+        };
+        Ok((header, r.bytes))
+    }
+}
+
+/// Vault file: master key wrapped with key derived from master password.
+/// magic | version | id | kdf id | kdf params | wrapped master key
+#[derive(Clone, Debug, PartialEq)]
+pub struct VaultHeader {
+    pub id: [u8; VAULT_ID_SIZE],
+    pub kdf: Kdf,
+    pub wrapped: WrappedKey
+}
+
+impl VaultHeader {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, AppError> {
+        let mut bytes = Vec::new();
+        bytes.write_all(&VAULT_MAGIC)?;
+        bytes.write_u8(VAULT_VERSION)?;
+        bytes.write_all(&self.id)?;
+        self.kdf.write(&mut bytes)?;
+        self.wrapped.write(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    pub fn read<R: Read>(r: &mut R) -> Result<Self, AppError> {
+        let mut magic = [0u8; VAULT_MAGIC.len()];
+        r.read_exact(&mut magic)?;
+        if magic != VAULT_MAGIC {
+            return Err(AppError::internal("File is not a vault"));
+        }
+
+        let version = r.read_u8()?;
+        if version != VAULT_VERSION {
+            return Err(AppError::UnsupportedFormat("vault version", version));
+        }
+
+        let mut id = [0u8; VAULT_ID_SIZE];
+        r.read_exact(&mut id)?;
+        Ok(VaultHeader {
+            id,
+            kdf: Kdf::read(r)?,
+            wrapped: WrappedKey::read(r)?
+        })
+    }
+}
+
+// Keeps read bytes, so header is authenticated exactly as it was written
+struct Recorder<'a, R: Read> {
+    inner: &'a mut R,
+    bytes: Vec<u8>
+}
+
+impl<'a, R: Read> Recorder<'a, R> {
+    fn new(inner: &'a mut R) -> Self {
+        Recorder {
+            inner,
+            bytes: Vec::new()
+        }
+    }
+}
+
+impl<'a, R: Read> Read for Recorder<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.bytes.extend_from_slice(&buf[..read]);
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argon2id() -> Kdf {
+        Kdf::Argon2id {
+            params: Argon2Params::default(),
+            salt: [1u8; SALT_SIZE]
+        }
+    }
+
+    fn wrapped() -> WrappedKey {
+        WrappedKey::wrap(&[2u8; 32], &Secret::new([3u8; 32])).unwrap()
+    }
+
+    #[test]
+    fn header_round_trip() {
+        let slots = vec![
+            Slot::Password { kdf: argon2id(), key_file: true, wrapped: wrapped() },
+            Slot::Vault { id: [4u8; VAULT_ID_SIZE], wrapped: wrapped() },
+            Slot::Recovery { kdf: argon2id(), wrapped: wrapped() }
+        ];
+        let header = Header::new(slots, true).unwrap();
+        let bytes = header.to_bytes().unwrap();
+
+        let mut input = bytes.clone();
+        input.extend_from_slice(b"encrypted text");
+        let (read, raw) = Header::read(&mut input.as_slice()).unwrap();
+        assert_eq!(read, header);
+        assert_eq!(raw, bytes);
+        assert!(read.requires_key_file());
+        assert!(read.in_vault());
+    }
+
+    #[test]
+    fn wrapped_key_round_trip() {
+        let key = wrapped().unwrap(&[2u8; 32]).unwrap();
+        assert_eq!(key.expose(), &[3u8; 32]);
+        assert!(wrapped().unwrap(&[5u8; 32]).is_err());
+    }
+
+    #[test]
+    fn version_1_header() {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&[1, Cipher::Aes256Gcm.id(), KEY_FILE_FLAG]);
+        argon2id().write(&mut bytes).unwrap();
+        bytes.extend_from_slice(&[6u8; gcm::NONCE_SIZE]);
+
+        let (header, raw) = Header::read(&mut bytes.as_slice()).unwrap();
+        assert_eq!(header.slots, vec![Slot::Direct { kdf: argon2id(), key_file: true }]);
+        assert_eq!(header.nonce, [6u8; gcm::NONCE_SIZE]);
+        assert!(!header.title);
+        assert_eq!(raw, bytes);
+    }
+
+    #[test]
+    fn unknown_version_is_rejected() {
+        let mut bytes = Header::new(vec![], false).unwrap().to_bytes().unwrap();
+        bytes[MAGIC.len()] = VERSION + 1;
+        assert!(matches!(Header::read(&mut bytes.as_slice()), Err(AppError::UnsupportedFormat("version", _))));
+    }
+
+    #[test]
+    fn invalid_kdf_parameters_are_rejected() {
+        let pbkdf2 = Kdf::Pbkdf2Sha3 {
+            iterations: 0,
+            salt: [1u8; SALT_SIZE]
+        };
+        let huge = Kdf::Argon2id {
+            params: Argon2Params { memory: u32::MAX, ..Argon2Params::default() },
+            salt: [1u8; SALT_SIZE]
+        };
+
+        for kdf in [pbkdf2, huge] {
+            let mut bytes = Vec::new();
+            kdf.write(&mut bytes).unwrap();
+            assert!(matches!(Kdf::read(&mut bytes.as_slice()), Err(AppError::UnsupportedFormat("kdf parameters", _))));
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,40 @@
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use crate::error::AppError;
+
+pub const NONCE_SIZE : usize = 12;
+
+pub struct Gcm {
+    cipher: Aes256Gcm
+}
+
+impl Gcm {
+    pub fn new(key: &[u8]) -> Self {
+        Gcm {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+        }
+    }
+
+    pub fn encrypt(&self, nonce: &[u8], aad: &[u8], data: &[u8]) -> Result<Vec<u8>, AppError> {
+        let payload = Payload {
+            msg: data,
+            aad
+        };
+
+        self.cipher
+            .encrypt(Nonce::from_slice(nonce), payload)
+            .map_err(|_| AppError::internal("Cannot encrypt data"))
+    }
+
+    pub fn decrypt(&self, nonce: &[u8], aad: &[u8], data: &[u8]) -> Result<Vec<u8>, AppError> {
+        let payload = Payload {
+            msg: data,
+            aad
+        };
+
+        // Tag mismatch means either wrong key or modified file, they are indistinguishable
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), payload)
+            .map_err(|_| AppError::InvalidPassword)
+    }
+}
\ No newline at end of file
@@ -1,14 +1,31 @@
 use getrandom::getrandom;
 use crate::error::AppError;
 
-pub fn iv() -> Result<[u8; 16], AppError> {
-    let mut iv = [0u8; 16];
-    getrandom(&mut iv)?;
-    Ok(iv)
-}
-
 pub fn bytes(size: usize) -> Result<Vec<u8>, AppError> {
     let mut entropy = vec![0; size];
     getrandom(&mut entropy)?;
     Ok(entropy)
+}
+
+/// Random 128 bit id in hex, used for file names
+pub fn hex_id() -> Result<String, AppError> {
+    Ok(bytes(16)?
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Uniform random index below `n`
+pub fn index(n: usize) -> Result<usize, AppError> {
+    let n = n as u32;
+    // Values above the last multiple of n are dropped, otherwise small indexes are more likely
+    let zone = u32::MAX - u32::MAX % n;
+    loop {
+        let mut bytes = [0u8; 4];
+        getrandom(&mut bytes)?;
+        let value = u32::from_le_bytes(bytes);
+        if value < zone {
+            return Ok((value % n) as usize);
+        }
+    }
 }
\ No newline at end of file
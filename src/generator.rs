@@ -0,0 +1,97 @@
+use crate::error::AppError;
+use crate::gen;
+use crate::secret::Secret;
+
+const LOWERCASE : &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE : &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS : &[u8] = b"0123456789";
+const SYMBOLS : &[u8] = b"!@#$%^&*()-_=+[]{};:,.<>?/";
+
+pub const MAX_PASSWORD_LENGTH : usize = 1024;
+pub const MAX_PASSPHRASE_WORDS : usize = 64;
+
+// Common short english words, one per line
+const WORDS : &str = include_str!("../assets/words.txt");
+
+#[derive(Clone, Copy, Debug)]
+pub struct Classes {
+    pub lowercase: bool,
+    pub uppercase: bool,
+    pub digits: bool,
+    pub symbols: bool
+}
+
+impl Classes {
+    fn selected(&self) -> Vec<&'static [u8]> {
+        let mut selected = Vec::new();
+        for (on, chars) in [(self.lowercase, LOWERCASE), (self.uppercase, UPPERCASE), (self.digits, DIGITS), (self.symbols, SYMBOLS)] {
+            if on {
+                selected.push(chars);
+            }
+        }
+        selected
+    }
+}
+
+/// Random password with at least one char of every selected class
+pub fn password(length: usize, classes: Classes) -> Result<Secret<String>, AppError> {
+    let selected = classes.selected();
+    if selected.is_empty() {
+        return Err(AppError::internal("No characters selected"));
+    }
+    if length < selected.len() {
+        return Err(AppError::internal("Password is too short"));
+    }
+    if length > MAX_PASSWORD_LENGTH {
+        return Err(AppError::internal("Password is too long"));
+    }
+
+    let mut bytes = Secret::new(Vec::with_capacity(length));
+    bytes.edit(|b| fill(b, length, &selected))?;
+    Secret::from_utf8(bytes.expose().clone())
+}
+
+fn fill(bytes: &mut Vec<u8>, length: usize, selected: &[&[u8]]) -> Result<(), AppError> {
+    let all: Vec<u8> = selected.concat();
+    for chars in selected {
+        bytes.push(chars[gen::index(chars.len())?]);
+    }
+    while bytes.len() < length {
+        bytes.push(all[gen::index(all.len())?]);
+    }
+
+    // Required chars are moved from the start to random places
+    for i in (1..bytes.len()).rev() {
+        bytes.swap(i, gen::index(i + 1)?);
+    }
+    Ok(())
+}
+
+/// Words picked from bundled list, like `maple-orbit-quartz-lunch`
+pub fn passphrase(count: usize, separator: &str) -> Result<Secret<String>, AppError> {
+    if count > MAX_PASSPHRASE_WORDS {
+        return Err(AppError::internal("Too many words"));
+    }
+    let words: Vec<&str> = WORDS.lines().collect();
+
+    let mut phrase = Secret::new(String::with_capacity(count * 9));
+    for i in 0..count {
+        let word = words[gen::index(words.len())?];
+        phrase.edit(|p| {
+            if i > 0 {
+                p.push_str(separator);
+            }
+            p.push_str(word);
+        });
+    }
+    Ok(phrase)
+}
+
+pub fn password_entropy(length: usize, classes: Classes) -> f64 {
+    let size: usize = classes.selected().iter().map(|c| c.len()).sum();
+    length as f64 * (size.max(1) as f64).log2()
+}
+
+pub fn passphrase_entropy(count: usize) -> f64 {
+    count as f64 * (WORDS.lines().count() as f64).log2()
+}
\ No newline at end of file
@@ -1,55 +1,166 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use argon2::{Algorithm, Argon2, Params, Version};
 use crypto::digest::Digest;
 use crypto::hmac::Hmac;
 use crypto::sha3::Sha3;
+use druid::Data;
+use serde::{Serialize, Deserialize};
 use crate::error::AppError;
+use crate::secret::Secret;
 
 pub const STORAGE_HASH_SIZE : usize = 48;
 
+// Argon2id memory costs in KiB
+const ARGON2_MAX_MEMORY : u32 = 256 * 1024;
+const ARGON2_MIN_MEMORY : u32 = 19 * 1024;
+const ARGON2_MAX_ITERATIONS : u32 = 64;
+const ARGON2_MAX_PARALLELISM : u32 = 16;
+
 const PRE_SALT : &str = "Bk8UdO46oAUe+nY1Dt5hMzazudCqegmrXta0/caJxQUjy6QcWU8FNbdAynFE+Qbyy6CYEY1sFfeTThN9IjU/u";
 const POST_SALT : &str = "+QYlwnvqv+O9oBfmpJNK2wYf7MsWEFvnJUh/aW75DbNJHpNO59D9b+NDVfU7uiP05oJzDsGVUhi5ig1YdDqjz";
 
-pub fn cypher(password: &str) -> [u8; 32] {
+pub fn cypher(password: &str) -> Secret<[u8; 32]> {
     let mut hasher = Sha3::sha3_256();
 
     hasher.input_str(PRE_SALT);
     hasher.input_str(password);
     hasher.input_str(POST_SALT);
 
-    let mut hex = [0u8; 32];
-    hasher.result(&mut hex);
+    let mut hex = Secret::zeroed();
+    hex.edit(|hex| hasher.result(hex));
+    hasher.reset();
     hex
 }
 
-pub fn storage_build(password: &str) -> Result<[u8; 48], AppError> {
-    let mut salt = [0u8; 16];
-    getrandom::getrandom(&mut salt)?;
+pub fn storage_check(password: &str, hash_bytes: &[u8]) -> Result<(), AppError> {
+    let salt = &hash_bytes[..16];
+    let input_dk = &hash_bytes[16..];
+
+    let dk = pbkdf2(password, salt, 100);
 
-    let mut dk = [0u8; 32];
+    if crypto::util::fixed_time_eq(input_dk, dk.expose()) {
+        Ok(())
+    } else {
+        Err(AppError::InvalidPassword)
+    }
+}
+
+pub fn pbkdf2(password: &str, salt: &[u8], iterations: u32) -> Secret<[u8; 32]> {
     let mut mac = Hmac::new(Sha3::sha3_256(), password.as_bytes());
+    let mut dk = Secret::zeroed();
+    dk.edit(|dk| crypto::pbkdf2::pbkdf2(&mut mac, salt, iterations, dk));
+    dk
+}
 
-    crypto::pbkdf2::pbkdf2(&mut mac, &salt[..], 100, &mut dk);
+pub fn key_file(path: &Path) -> Result<Secret<[u8; 32]>, AppError> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha3::sha3_256();
 
-    let mut result = [0u8; STORAGE_HASH_SIZE];
-    for i in 0..16 {
-        result[i] = salt[i];
+    let mut buf = [0u8; 8192];
+    loop {
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        hasher.input(&buf[..read]);
     }
-    for i in 0..32 {
-        result[i + salt.len()] = dk[i];
+
+    let mut hash = Secret::zeroed();
+    hash.edit(|hash| hasher.result(hash));
+    hasher.reset();
+    Ok(hash)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Data, PartialEq)]
+pub struct Argon2Params {
+    pub memory: u32,
+    pub iterations: u32,
+    pub parallelism: u32
+}
+
+impl Default for Argon2Params {
+    fn default() -> Self {
+        Argon2Params {
+            memory: 64 * 1024,
+            iterations: 3,
+            parallelism: 1
+        }
     }
-    Ok(result)
 }
 
-pub fn storage_check(password: &str, hash_bytes: &[u8]) -> Result<(), AppError> {
-    let salt = &hash_bytes[..16];
-    let input_dk = &hash_bytes[16..];
+impl Argon2Params {
+    /// Parameters read from a file are limited, so a crafted file can't exhaust memory when opened
+    pub fn is_supported(&self) -> bool {
+        (1..=ARGON2_MAX_PARALLELISM).contains(&self.parallelism)
+            && (8 * self.parallelism..=ARGON2_MAX_MEMORY).contains(&self.memory)
+            && (1..=ARGON2_MAX_ITERATIONS).contains(&self.iterations)
+    }
 
-    let mut mac = Hmac::new(Sha3::sha3_256(), password.as_bytes());
-    let mut dk = [0u8; 32];
-    crypto::pbkdf2::pbkdf2(&mut mac, &salt[..], 100, &mut dk);
+    /// Nearest supported parameters, so files written with them can be read back
+    pub fn clamped(self) -> Self {
+        let parallelism = self.parallelism.clamp(1, ARGON2_MAX_PARALLELISM);
+        Argon2Params {
+            memory: self.memory.clamp(8 * parallelism, ARGON2_MAX_MEMORY),
+            iterations: self.iterations.clamp(1, ARGON2_MAX_ITERATIONS),
+            parallelism
+        }
+    }
+}
 
-    if crypto::util::fixed_time_eq(input_dk, &dk) {
-        Ok(())
-    } else {
-        Err(AppError::InvalidPassword)
+/// Key file hash is passed as Argon2 secret input, so neither factor alone is enough
+pub fn argon2id(password: &str, key_file: Option<&[u8]>, salt: &[u8], params: &Argon2Params) -> Result<Secret<[u8; 32]>, AppError> {
+    let params = Params::new(params.memory, params.iterations, params.parallelism, Some(32))?;
+    let argon2 = match key_file {
+        Some(key_file) => Argon2::new_with_secret(key_file, Algorithm::Argon2id, Version::V0x13, params)?,
+        None => Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+    };
+
+    let mut dk = Secret::zeroed();
+    dk.edit(|dk| argon2.hash_password_into(password.as_bytes(), salt, dk))?;
+    Ok(dk)
+}
+
+/// Picks Argon2id parameters so that derivation takes roughly `target` on this machine.
+/// Memory is lowered first when even single pass is too slow, then iterations are scaled.
+pub fn calibrate(target: Duration) -> Result<Argon2Params, AppError> {
+    let salt = [0u8; 16];
+    let mut params = Argon2Params {
+        memory: ARGON2_MAX_MEMORY,
+        iterations: 1,
+        parallelism: 1
+    };
+
+    loop {
+        let start = Instant::now();
+        argon2id("calibration", None, &salt, &params)?;
+        let elapsed = start.elapsed();
+
+        if elapsed > target && params.memory > ARGON2_MIN_MEMORY {
+            params.memory = u32::max(params.memory / 2, ARGON2_MIN_MEMORY);
+            continue;
+        }
+
+        let passes = target.as_secs_f64() / elapsed.as_secs_f64().max(f64::EPSILON);
+        params.iterations = (passes as u32).clamp(1, ARGON2_MAX_ITERATIONS);
+        return Ok(params);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamped_params_are_supported() {
+        let huge = Argon2Params { memory: u32::MAX, iterations: u32::MAX, parallelism: u32::MAX };
+        let zero = Argon2Params { memory: 0, iterations: 0, parallelism: 0 };
+        for params in [huge, zero] {
+            assert!(!params.is_supported());
+            assert!(params.clamped().is_supported());
+        }
+        assert_eq!(Argon2Params::default().clamped(), Argon2Params::default());
     }
 }
\ No newline at end of file
@@ -0,0 +1,213 @@
+use std::{env, fs};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use crate::error::AppError;
+use crate::exe_directory;
+
+pub const DATA_DIR_VARIABLE : &str = "NOTEPAD_DATA_DIR";
+// Empty file next to executable that turns portable mode on
+const PORTABLE_MARKER : &str = "portable";
+const APP_DIR : &str = "notepad";
+const DOCS_DIR : &str = "docs";
+const TRASH_DIR : &str = "trash";
+const HISTORY_DIR : &str = "history";
+const MIGRATED : [&str; 2] = ["config.json", "credentials.json"];
+
+static PATHS : OnceLock<Paths> = OnceLock::new();
+
+/// Where docs, config.json, credentials.json and temp files are kept
+pub enum Location {
+    /// XDG data and config directories, or their analogs on other platforms
+    Default,
+    /// Everything next to executable, as in old versions
+    Portable,
+    /// Everything in given directory
+    Custom(PathBuf)
+}
+
+struct Paths {
+    data: PathBuf,
+    config: PathBuf
+}
+
+impl Location {
+    /// Directory from arguments wins over environment variable, which wins over portable mode
+    pub fn resolve(data_dir: Option<PathBuf>, portable: bool) -> Result<Self, AppError> {
+        if let Some(dir) = data_dir {
+            return Ok(Location::Custom(dir));
+        }
+
+        if let Some(dir) = env::var_os(DATA_DIR_VARIABLE).filter(|d| !d.is_empty()) {
+            return Ok(Location::Custom(PathBuf::from(dir)));
+        }
+
+        if portable || exe_directory()?.join(PORTABLE_MARKER).exists() {
+            return Ok(Location::Portable);
+        }
+
+        Ok(Location::Default)
+    }
+
+    /// Parses `--data-dir <path>` and `--portable`, other arguments are ignored
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, AppError> {
+        let mut data_dir = None;
+        let mut portable = false;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--data-dir" => data_dir = args.next().map(PathBuf::from),
+                "--portable" => portable = true,
+                _ => {}
+            }
+        }
+
+        Location::resolve(data_dir, portable)
+    }
+}
+
+/// Must be called once before any state is loaded
+pub fn init(location: Location) -> Result<(), AppError> {
+    let paths = match location {
+        Location::Default => {
+            let data = dirs::data_dir().ok_or(AppError::internal("Data directory not found"))?;
+            let config = dirs::config_dir().ok_or(AppError::internal("Config directory not found"))?;
+            let paths = Paths {
+                data: data.join(APP_DIR),
+                config: config.join(APP_DIR)
+            };
+
+            fs::create_dir_all(&paths.data)?;
+            fs::create_dir_all(&paths.config)?;
+            migrate(&exe_directory()?, &paths)?;
+            paths
+        },
+        Location::Portable => Paths {
+            data: exe_directory()?,
+            config: exe_directory()?
+        },
+        Location::Custom(dir) => {
+            fs::create_dir_all(&dir)?;
+            Paths {
+                data: dir.clone(),
+                config: dir
+            }
+        }
+    };
+
+    PATHS.set(paths).map_err(|_| AppError::internal("Paths are already initialized"))
+}
+
+/// Tests of one run share temp data folder, paths can't be initialized twice
+#[cfg(test)]
+pub fn init_for_test() {
+    static INIT : std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| {
+        let dir = env::temp_dir().join(format!("notepad-test-{}", std::process::id()));
+        init(Location::Custom(dir)).unwrap();
+    });
+}
+
+pub fn data_directory() -> Result<PathBuf, AppError> {
+    Ok(paths()?.data.clone())
+}
+
+pub fn config_directory() -> Result<PathBuf, AppError> {
+    Ok(paths()?.config.clone())
+}
+
+pub fn docs_directory() -> Result<PathBuf, AppError> {
+    Ok(data_directory()?.join(DOCS_DIR))
+}
+
+pub fn trash_directory() -> Result<PathBuf, AppError> {
+    Ok(data_directory()?.join(TRASH_DIR))
+}
+
+pub fn history_directory() -> Result<PathBuf, AppError> {
+    Ok(data_directory()?.join(HISTORY_DIR))
+}
+
+/// Path relative to root with `/` separators, notes and folders are identified by it on every platform
+pub fn relative(root: &Path, path: &Path) -> Result<String, AppError> {
+    let components: Option<Vec<&str>> = path
+        .strip_prefix(root).map_err(|_| AppError::internal("Path is outside of data folder"))?
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect();
+
+    Ok(components.ok_or(AppError::internal("Invalid file name"))?.join("/"))
+}
+
+/// Tells if both paths lead to the same existing file, names may differ in case on case insensitive file systems
+#[cfg(not(windows))]
+pub fn same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false
+    }
+}
+
+#[cfg(windows)]
+pub fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false
+    }
+}
+
+fn paths() -> Result<&'static Paths, AppError> {
+    PATHS.get().ok_or(AppError::internal("Paths are not initialized"))
+}
+
+// Moves data of exe relative layout, files that already exist in new place are not touched
+fn migrate(old: &Path, paths: &Paths) -> Result<(), AppError> {
+    let docs = paths.data.join(DOCS_DIR);
+    if old.join(DOCS_DIR).is_dir() && !docs.exists() {
+        move_path(&old.join(DOCS_DIR), &docs)?;
+    }
+
+    for name in MIGRATED {
+        let config = paths.config.join(name);
+        if old.join(name).is_file() && !config.exists() {
+            move_path(&old.join(name), &config)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Rename fails across file systems, so data is copied and then removed if install folder is writable
+fn move_path(from: &Path, to: &Path) -> Result<(), AppError> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    if from.is_dir() {
+        // Copied into temp folder first, so interrupted migration is started again on next run
+        let temp = to.with_extension("migrating");
+        copy_dir(from, &temp)?;
+        fs::rename(&temp, to)?;
+        let _ = fs::remove_dir_all(from);
+    } else {
+        fs::copy(from, to)?;
+        let _ = fs::remove_file(from);
+    }
+
+    Ok(())
+}
+
+// Notebook folders are copied with their content, any error stops migration before old data is removed
+fn copy_dir(from: &Path, to: &Path) -> Result<(), AppError> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            copy_dir(&path, &to.join(entry.file_name()))?;
+        } else {
+            fs::copy(&path, to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,89 @@
+use getrandom::getrandom;
+use zeroize::Zeroize;
+use crate::error::AppError;
+use crate::secret::Secret;
+
+// RFC 4648 base32, without padding
+const ALPHABET : &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const CODE_BYTES : usize = 20;
+const CODE_LEN : usize = CODE_BYTES * 8 / 5;
+const GROUP_LEN : usize = 4;
+
+/// New recovery code with 160 bits of entropy, like `ABCD-EFGH-...-2345`
+pub fn generate() -> Result<Secret<String>, AppError> {
+    let mut bytes = [0u8; CODE_BYTES];
+    getrandom(&mut bytes)?;
+
+    let mut code = Secret::new(String::with_capacity(CODE_LEN + CODE_LEN / GROUP_LEN));
+    code.edit(|code| {
+        let mut buffer = 0u16;
+        let mut bits = 0;
+        for byte in bytes {
+            buffer = (buffer << 8) | byte as u16;
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                if !code.is_empty() && code.len() % (GROUP_LEN + 1) == GROUP_LEN {
+                    code.push('-');
+                }
+                code.push(ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+            }
+        }
+        buffer.zeroize();
+    });
+    bytes.zeroize();
+
+    Ok(code)
+}
+
+/// Code as it is used for key derivation, None if input can't be a recovery code
+pub fn normalize(input: &str) -> Option<Secret<String>> {
+    let mut code = Secret::new(String::with_capacity(input.len()));
+    code.edit(|code| {
+        for c in input.chars() {
+            if c == '-' || c.is_whitespace() {
+                continue;
+            }
+            code.push(c.to_ascii_uppercase());
+        }
+    });
+
+    let expose = code.expose();
+    if expose.len() == CODE_LEN && expose.bytes().all(|b| ALPHABET.contains(&b)) {
+        Some(code)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_code_is_grouped() {
+        let code = generate().unwrap();
+        let groups: Vec<&str> = code.expose().split('-').collect();
+        assert_eq!(groups.len(), CODE_LEN / GROUP_LEN);
+        assert!(groups.iter().all(|g| g.len() == GROUP_LEN));
+    }
+
+    #[test]
+    fn normalize_round_trip() {
+        let code = generate().unwrap();
+        let normalized = normalize(code.expose()).unwrap();
+        assert_eq!(normalized.expose(), &code.expose().replace('-', ""));
+
+        let typed = code.expose().to_lowercase().replace('-', " ");
+        assert_eq!(normalize(&typed).unwrap().expose(), normalized.expose());
+    }
+
+    #[test]
+    fn invalid_codes_are_rejected() {
+        assert!(normalize("").is_none());
+        assert!(normalize("ABCD-EFGH").is_none());
+        // 0, 1 and 8 are not in the alphabet
+        assert!(normalize("ABCD-EFGH-IJKL-MNOP-QRST-UVWX-YZ23-4018").is_none());
+        assert!(normalize("ABCD-EFGH-IJKL-MNOP-QRST-UVWX-YZ23-4567").is_some());
+    }
+}
\ No newline at end of file
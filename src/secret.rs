@@ -0,0 +1,111 @@
+use std::fmt::{Debug, Formatter};
+use std::fs::File;
+use druid::Data;
+use zeroize::Zeroize;
+use crate::error::AppError;
+
+/// Holds sensitive value on heap and wipes it on drop.
+/// With "mlock" feature its memory is also locked, so it is not swapped to disk.
+pub struct Secret<T: Zeroize + AsRef<[u8]>> {
+    value: Box<T>,
+    #[cfg(feature = "mlock")]
+    lock: Option<region::LockGuard>
+}
+
+impl<T: Zeroize + AsRef<[u8]>> Secret<T> {
+    pub fn new(value: T) -> Self {
+        let mut secret = Secret {
+            value: Box::new(value),
+            #[cfg(feature = "mlock")]
+            lock: None
+        };
+        secret.lock();
+        secret
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.value
+    }
+
+    /// Changes value in place, so no copies are left behind
+    pub fn edit<R>(&mut self, f: impl FnOnce(&mut T) -> R) -> R {
+        let r = f(&mut self.value);
+        self.lock();
+        r
+    }
+
+    #[cfg(feature = "mlock")]
+    fn lock(&mut self) {
+        self.lock = None;
+
+        let bytes: &[u8] = (*self.value).as_ref();
+        if !bytes.is_empty() {
+            // Best effort, fails when limit of locked memory is reached
+            self.lock = region::lock(bytes.as_ptr(), bytes.len()).ok();
+        }
+    }
+
+    #[cfg(not(feature = "mlock"))]
+    fn lock(&mut self) {
+    }
+}
+
+impl Secret<[u8; 32]> {
+    // Filled through edit, moving filled array into secret would leave its copy on stack
+    pub fn zeroed() -> Self {
+        Secret::new([0u8; 32])
+    }
+}
+
+impl Secret<String> {
+    pub fn empty() -> Self {
+        Secret::new(String::new())
+    }
+
+    pub fn from_utf8(bytes: Vec<u8>) -> Result<Self, AppError> {
+        match String::from_utf8(bytes) {
+            Ok(text) => Ok(Secret::new(text)),
+            Err(e) => {
+                let error = e.utf8_error();
+                e.into_bytes().zeroize();
+                Err(AppError::Utf8(error))
+            }
+        }
+    }
+}
+
+/// Buffer for text decrypted from file.
+/// Text is never longer than the file, so buffer is not reallocated with text copies left behind.
+pub fn text_buffer(file: &File) -> Result<Vec<u8>, AppError> {
+    Ok(Vec::with_capacity(file.metadata()?.len() as usize))
+}
+
+impl<T: Zeroize + AsRef<[u8]>> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+impl<T: Zeroize + AsRef<[u8]> + Clone> Clone for Secret<T> {
+    fn clone(&self) -> Self {
+        Secret::new((*self.value).clone())
+    }
+}
+
+impl<T: Zeroize + AsRef<[u8]> + PartialEq> PartialEq for Secret<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Zeroize + AsRef<[u8]> + PartialEq + Clone + 'static> Data for Secret<T> {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl<T: Zeroize + AsRef<[u8]>> Debug for Secret<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Secret(***)")
+    }
+}
\ No newline at end of file
@@ -1,16 +1,167 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{DirBuilder, File, OpenOptions, Permissions};
+use std::io::{Read, Write};
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt};
+use dbus_secret_service::{EncryptionType, SecretService};
+use zeroize::Zeroize;
 use crate::AppError;
+use crate::{gcm, paths};
+use crate::gcm::Gcm;
+use crate::secret::Secret;
 use crate::secure::CryptBackend;
 
+// Data stored before this backend existed has no magic and is not encrypted
+const MAGIC : [u8; 4] = [0x89, b'N', b'P', b'S'];
+
+thread_local! {
+    // Secret Service is asked once, so D-Bus connection is not opened on every save
+    static SERVICE_KEY : RefCell<Option<Option<Secret<[u8; 32]>>>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug)]
 pub struct CrossCryptBackend;
 
+/// Where the per-user key lives, recorded in encrypted data,
+/// so it is decrypted with the same key even if Secret Service goes away.
+#[derive(Clone, Copy)]
+enum KeyStore {
+    SecretService,
+    File
+}
+
+impl KeyStore {
+    fn id(&self) -> u8 {
+        match self {
+            KeyStore::SecretService => 1,
+            KeyStore::File => 2
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self, AppError> {
+        match id {
+            1 => Ok(KeyStore::SecretService),
+            2 => Ok(KeyStore::File),
+            _ => Err(AppError::UnsupportedFormat("key store", id))
+        }
+    }
+
+    fn key(&self) -> Result<Secret<[u8; 32]>, AppError> {
+        match self {
+            KeyStore::SecretService => SERVICE_KEY.with(|cache| {
+                cache
+                    .borrow_mut()
+                    .get_or_insert_with(|| KeyStore::secret_service_key().ok())
+                    .clone()
+                    .ok_or(AppError::internal("Secret Service is not available"))
+            }),
+            KeyStore::File => KeyStore::file_key()
+        }
+    }
+
+    fn secret_service_key() -> Result<Secret<[u8; 32]>, AppError> {
+        let service = SecretService::connect(EncryptionType::Dh)?;
+        let collection = service.get_default_collection()?;
+        collection.ensure_unlocked()?;
+
+        let attributes = HashMap::from([("application", "notepad"), ("purpose", "credentials")]);
+        if let Some(item) = collection.search_items(attributes.clone())?.first() {
+            item.ensure_unlocked()?;
+            let mut bytes = item.get_secret()?;
+            if bytes.len() != 32 {
+                bytes.zeroize();
+                return Err(AppError::internal("Invalid credentials key"));
+            }
+
+            let mut key = Secret::zeroed();
+            key.edit(|key| key.copy_from_slice(&bytes));
+            bytes.zeroize();
+            return Ok(key);
+        }
+
+        let key = KeyStore::generate()?;
+        collection.create_item("Notepad credentials key", attributes, key.expose(), true, "application/octet-stream")?;
+        Ok(key)
+    }
+
+    fn file_key() -> Result<Secret<[u8; 32]>, AppError> {
+        let dir = paths::config_directory()?;
+        let path = dir.join("credentials.key");
+
+        if path.exists() {
+            let mut file = File::open(&path)?;
+            if file.metadata()?.permissions().mode() & 0o077 != 0 {
+                std::fs::set_permissions(&path, Permissions::from_mode(0o600))?;
+            }
+
+            let mut key = Secret::zeroed();
+            key.edit(|key| file.read_exact(key))?;
+            return Ok(key);
+        }
+
+        DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(&dir)?;
+
+        let key = KeyStore::generate()?;
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)?;
+        file.write_all(key.expose())?;
+        file.sync_all()?;
+        Ok(key)
+    }
+
+    fn generate() -> Result<Secret<[u8; 32]>, AppError> {
+        let mut key = Secret::zeroed();
+        key.edit(|key| getrandom::getrandom(key))?;
+        Ok(key)
+    }
+}
+
 impl CryptBackend for CrossCryptBackend {
     fn crypt(data: &mut [u8]) -> Result<Vec<u8>, AppError> {
-        // TODO: crypt
-        Ok(Vec::from(data))
+        // Secret Service is preferred, key file is used when it is not running
+        let (store, key) = match KeyStore::SecretService.key() {
+            Ok(key) => (KeyStore::SecretService, key),
+            Err(_) => (KeyStore::File, KeyStore::File.key()?)
+        };
+
+        let mut r = Vec::new();
+        r.write_all(&MAGIC)?;
+        r.write_all(&[store.id()])?;
+
+        let mut nonce = [0u8; gcm::NONCE_SIZE];
+        getrandom::getrandom(&mut nonce)?;
+        let encrypted = Gcm::new(key.expose()).encrypt(&nonce, &r, data)?;
+
+        r.write_all(&nonce)?;
+        r.write_all(&encrypted)?;
+        Ok(r)
     }
 
     fn decrypt(data: &mut [u8]) -> Result<Vec<u8>, AppError> {
-        // TODO: decrypt
-        Ok(Vec::from(data))
+        if CrossCryptBackend::is_outdated(data) {
+            return Ok(Vec::from(data));
+        }
+
+        let header_size = MAGIC.len() + 1;
+        if data.len() < header_size + gcm::NONCE_SIZE {
+            return Err(AppError::internal("Invalid encrypted data"));
+        }
+
+        let (header, rest) = data.split_at(header_size);
+        let (nonce, encrypted) = rest.split_at(gcm::NONCE_SIZE);
+        let key = KeyStore::from_id(header[MAGIC.len()])?.key()?;
+        Gcm::new(key.expose())
+            .decrypt(nonce, header, encrypted)
+            .map_err(|_| AppError::internal("Credentials key does not match"))
+    }
+
+    fn is_outdated(data: &[u8]) -> bool {
+        !data.starts_with(&MAGIC)
     }
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
 pub mod windows;
+#[cfg(not(windows))]
 pub mod cross;
 
 use std::fmt;
@@ -9,7 +10,11 @@ use base64::engine::general_purpose;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::{DeserializeOwned, Visitor};
 use crate::AppError;
+
+#[cfg(windows)]
 use crate::secure::windows::WindowsCryptBackend;
+#[cfg(not(windows))]
+use crate::secure::cross::CrossCryptBackend;
 
 macro_rules! tri {
     ($i:ident, $e:expr $(,)?) => {
@@ -34,6 +39,7 @@ pub struct _Secure<B, V>
 {
     backend: PhantomData<B>,
     value: V,
+    outdated: bool
 }
 
 impl<B, V> _Secure<B, V>
@@ -44,7 +50,8 @@ impl<B, V> _Secure<B, V>
     pub fn new(value: V) -> Self {
         _Secure {
             backend: PhantomData::default(),
-            value
+            value,
+            outdated: false
         }
     }
 
@@ -55,6 +62,11 @@ impl<B, V> _Secure<B, V>
     pub fn value_mut(&mut self) -> &mut V {
         &mut self.value
     }
+
+    /// Value was stored in format backend doesn't write anymore, it should be saved again
+    pub fn is_outdated(&self) -> bool {
+        self.outdated
+    }
 }
 
 impl<B, V> Serialize for _Secure<B, V>
@@ -84,9 +96,14 @@ impl<'de, B, V> Deserialize<'de> for _Secure<B, V>
     {
         let base64 = deserializer.deserialize_string(StringVisitor)?;
         let mut bytes = tri!(de, general_purpose::STANDARD.decode(base64));
+        let outdated = B::is_outdated(&bytes);
         let decrypted = tri!(de, B::decrypt(&mut bytes));
         let value = tri!(de, serde_json::from_slice::<V>(&decrypted));
-        Ok(_Secure::new(value))
+        Ok(_Secure {
+            backend: PhantomData,
+            value,
+            outdated
+        })
     }
 }
 
@@ -117,4 +134,5 @@ impl<'de> Visitor<'de> for StringVisitor {
 pub trait CryptBackend {
     fn crypt(data: &mut [u8]) -> Result<Vec<u8>, AppError>;
     fn decrypt(data: &mut [u8]) -> Result<Vec<u8>, AppError>;
+    fn is_outdated(data: &[u8]) -> bool;
 }
\ No newline at end of file
@@ -94,4 +94,8 @@ impl CryptBackend for WindowsCryptBackend {
             Err(AppError::internal("Error on encrypt"))
         }
     }
+
+    fn is_outdated(_data: &[u8]) -> bool {
+        false
+    }
 }
\ No newline at end of file
@@ -1,71 +1,412 @@
+use std::path::Path;
 use druid::{Data, Lens};
 use druid::im::Vector;
 use crate::backups::Backup;
 use crate::error::AppError;
-use crate::state;
+use crate::{export, state};
+use crate::secret::Secret;
 use crate::state::config::Config;
+use crate::state::generator::Generator;
+use crate::state::history::Version;
+use crate::state::journal::{self, Entry};
+use crate::state::key::{Factors, Key};
 use crate::state::new_tab::NewTab;
+use crate::state::password_form::PasswordForm;
 use crate::state::tabs::Tabs;
+use crate::state::trash::Trash;
+use crate::state::vault::Vault;
+use crate::ui::password_text::PasswordText;
 
 #[derive(Clone, Data, Lens)]
 pub struct AppState {
     pub config: Config,
     pub new_tab: NewTab,
+    pub password_form: PasswordForm,
     pub tabs: Tabs,
+    pub trash: Trash,
+    pub vault: Vault,
+    pub generator: Generator,
+    // Kept only in memory, so it is entered once per session
+    pub backup_passphrase: PasswordText,
+    pub new_folder: String,
+    pub new_name: String,
+    // Versions of note shown in history window
+    pub versions: Vector<Version>,
+    pub selected_version: Option<String>,
+    // Unsaved edits left by unclean shutdown
+    pub recovery: Vector<Entry>,
+    pub selected_recovery: Option<String>,
     pub to_remove: Vector<String>
 }
 
 impl AppState {
     pub fn load() -> Result<Self, AppError> {
+        let mut config = Config::load()?;
+        // Remove "kdf" from config.json to calibrate again
+        if config.kdf.is_none() {
+            config.calibrate()?;
+            config.save()?;
+        }
+        let mut state = Self::new(config, Tabs::load()?)?;
+        state.trash.purge_expired(state.config.auto_purge_days)?;
+        state.recovery = Vector::from(journal::recover()?);
+        state.selected_recovery = state.recovery
+            .front()
+            .map(|e| e.id.clone());
+        Ok(state)
+    }
+
+    /// Loads state without calibration, trash purge and journal recovery done on app start
+    pub fn open() -> Result<Self, AppError> {
+        Self::new(Config::load()?, Tabs::open()?)
+    }
+
+    fn new(config: Config, tabs: Tabs) -> Result<Self, AppError> {
         Ok(AppState {
-            config: Config::load()?,
+            config,
             new_tab: NewTab::new(),
-            tabs: Tabs::load()?,
+            password_form: PasswordForm::new(),
+            tabs,
+            trash: Trash::load()?,
+            vault: Vault::load()?,
+            generator: Generator::new(),
+            backup_passphrase: PasswordText::new(),
+            new_folder: String::new(),
+            new_name: String::new(),
+            versions: Vector::new(),
+            selected_version: None,
+            recovery: Vector::new(),
+            selected_recovery: None,
             to_remove: Vector::new()
         })
     }
 
     pub fn add_new_clear_tab(&mut self) -> Result<(), AppError> {
-        let (name, _) = self.new_tab.take();
-        self.tabs.add(name, None)
+        let (name, _, _) = self.new_tab.take();
+        self.tabs.add(name, None, false)?;
+        Ok(())
+    }
+
+    pub fn check_new_tab_password(&self) -> Result<(), AppError> {
+        let password = self.new_tab.password.value();
+        if password != self.new_tab.repeat.value() {
+            return Err(AppError::PasswordsMismatch);
+        }
+        // Tab stored in vault may have no password of its own
+        if self.new_tab.vault && password.is_empty() {
+            return Ok(());
+        }
+        self.config.check_password(password)
+    }
+
+    pub fn create_folder(&mut self, parent: &str) -> Result<(), AppError> {
+        let name = std::mem::take(&mut self.new_folder);
+        self.tabs.create_folder(parent, &name)
+    }
+
+    pub fn remove_tab(&mut self, key: u64) -> Result<(), AppError> {
+        self.trash.put(self.tabs.get(key))?;
+        self.tabs.remove(key)
+    }
+
+    pub fn restore_from_trash(&mut self, id: &str) -> Result<(), AppError> {
+        let key = self.trash.restore(id, &mut self.tabs)?;
+        let folder = self.tabs.get(key).folder().to_string();
+        self.tabs.set_folder(&folder);
+        Ok(())
+    }
+
+    pub fn load_versions(&mut self, key: u64) -> Result<(), AppError> {
+        self.versions = Vector::from(self.tabs.get(key).versions()?);
+        self.selected_version = self.versions
+            .front()
+            .map(|v| v.id.clone());
+        Ok(())
+    }
+
+    pub fn restore_version(&mut self, key: u64) -> Result<(), AppError> {
+        let id = self.selected_version
+            .clone()
+            .ok_or(AppError::internal("Version is not selected"))?;
+        self.tabs.restore_version(key, &id, &self.config.history_retention())?;
+        self.load_versions(key)
+    }
+
+    /// Replaces text of note with unsaved one, protected note must be unlocked first
+    pub fn recover_note(&mut self, id: &str) -> Result<(), AppError> {
+        let entry = self.recovery_entry(id)?;
+        let key = self.tabs
+            .find(&entry.file)
+            .ok_or(AppError::internal("Note not found"))?;
+        self.tabs
+            .get_mut(key)
+            .recover(&journal::recovery_path(id)?)?;
+        self.discard_recovery(id)
+    }
+
+    pub fn discard_recovery(&mut self, id: &str) -> Result<(), AppError> {
+        let entries: Vec<Entry> = self.recovery
+            .iter()
+            .cloned()
+            .collect();
+        journal::discard(id, &entries)?;
+
+        self.recovery.retain(|e| e.id != id);
+        self.selected_recovery = self.recovery
+            .front()
+            .map(|e| e.id.clone());
+        Ok(())
+    }
+
+    pub fn recovery_entry(&self, id: &str) -> Result<Entry, AppError> {
+        self.recovery
+            .iter()
+            .find(|e| e.id == id)
+            .cloned()
+            .ok_or(AppError::internal("Invalid recovery entry"))
+    }
+
+    pub fn rename_tab(&mut self, key: u64) -> Result<(), AppError> {
+        let name = std::mem::take(&mut self.new_name);
+        self.tabs.rename(key, &name)
+    }
+
+    pub fn use_generated_password(&mut self) {
+        let password = self.generator.take();
+        self.new_tab.password.set(password.expose());
+        self.new_tab.repeat.set(password.expose());
+    }
+
+    /// Returns recovery code of the tab, it is shown only once
+    pub fn add_new_protected_tab(&mut self) -> Result<Secret<String>, AppError> {
+        self.check_new_tab_password()?;
+        let in_vault = self.new_tab.vault;
+        let hide_name = self.new_tab.hide_name;
+        let (name, password, key_file) = self.new_tab.take();
+        let key_file = key_file.hash()?;
+        let params = self.config.kdf();
+
+        let mut key = Key::generate()?;
+        // Tab stored in vault may have no password of its own
+        if !in_vault || !password.expose().is_empty() || key_file.is_some() {
+            key.set_password(&Factors::new(password, key_file), &params)?;
+        }
+        if in_vault {
+            key.add_to_vault(&self.vault)?;
+        }
+        let code = key.new_recovery_code(&params)?;
+
+        self.tabs.add(name, Some(key), hide_name)?;
+        Ok(code)
+    }
+
+    pub fn open_tab(&mut self, key: u64) -> Result<(), AppError> {
+        let params = self.config.kdf();
+
+        // Master password typed for a vault tab unlocks the whole vault
+        let tab = self.tabs.get(key);
+        if tab.in_vault() && self.vault.exists() && !self.vault.unlocked() && self.vault.unlock(tab.entered_password()).is_ok() {
+            return self.tabs.open_vault(&params, &self.vault);
+        }
+
+        self.tabs
+            .get_mut(key)
+            .open(&params, &self.vault)
+    }
+
+    pub fn change_password(&mut self, key: u64) -> Result<(), AppError> {
+        let (current, password) = self.password_form.take()?;
+        self.config.check_password(password.expose())?;
+        let params = self.config.kdf();
+        self.tabs
+            .get_mut(key)
+            .change_password(current.expose(), password, &params, &self.vault)
+    }
+
+    pub fn protect_tab(&mut self, key: u64) -> Result<Secret<String>, AppError> {
+        let (_, password) = self.password_form.take()?;
+        self.config.check_password(password.expose())?;
+        let params = self.config.kdf();
+
+        let mut tab_key = Key::generate()?;
+        tab_key.set_password(&Factors::password(password), &params)?;
+        let code = tab_key.new_recovery_code(&params)?;
+
+        let tab = self.tabs.get_mut(key);
+        tab.protect(tab_key)?;
+        let file = tab.file.clone();
+
+        // Journal and recovery copies written before are clear text, they are wiped with the tab
+        self.tabs.write_journal()?;
+        let copies: Vec<String> = self.recovery
+            .iter()
+            .filter(|e| e.file == file)
+            .map(|e| e.id.clone())
+            .collect();
+        for id in copies {
+            self.discard_recovery(&id)?;
+        }
+        Ok(code)
+    }
+
+    pub fn new_recovery_code(&mut self, key: u64) -> Result<Secret<String>, AppError> {
+        let params = self.config.kdf();
+        self.tabs
+            .get_mut(key)
+            .new_recovery_code(&params)
+    }
+
+    pub fn unprotect_tab(&mut self, key: u64) -> Result<(), AppError> {
+        let (current, _) = self.password_form.take()?;
+        self.tabs
+            .get_mut(key)
+            .unprotect(current.expose(), &self.vault)
+    }
+
+    pub fn lock_tab(&mut self, key: u64) -> Result<(), AppError> {
+        self.tabs
+            .get_mut(key)
+            .lock()
+    }
+
+    pub fn hide_tab_name(&mut self, key: u64) -> Result<(), AppError> {
+        self.tabs
+            .get_mut(key)
+            .hide_name()
+    }
+
+    pub fn show_tab_name(&mut self, key: u64) -> Result<(), AppError> {
+        self.tabs
+            .get_mut(key)
+            .show_name()
+    }
+
+    pub fn add_tab_to_vault(&mut self, key: u64) -> Result<(), AppError> {
+        self.tabs
+            .get_mut(key)
+            .add_to_vault(&self.vault)
+    }
+
+    pub fn lock_all(&mut self) -> Result<(), AppError> {
+        self.tabs.lock_all()?;
+        self.vault.lock();
+        Ok(())
+    }
+
+    pub fn create_vault(&mut self) -> Result<(), AppError> {
+        let (_, password) = self.password_form.take()?;
+        self.vault.create(password.expose(), &self.config.kdf())
+    }
+
+    pub fn unlock_vault(&mut self) -> Result<(), AppError> {
+        let (current, _) = self.password_form.take()?;
+        self.vault.unlock(current.expose())?;
+        self.tabs.open_vault(&self.config.kdf(), &self.vault)
+    }
+
+    pub fn change_vault_password(&mut self) -> Result<(), AppError> {
+        let (current, password) = self.password_form.take()?;
+        self.vault.change_password(current.expose(), password.expose(), &self.config.kdf())
+    }
+
+    pub fn export_tab(&mut self, key: u64, path: &Path) -> Result<(), AppError> {
+        let (_, passphrase) = self.password_form.take()?;
+        if passphrase.expose().is_empty() {
+            return Err(AppError::EmptyPassword);
+        }
+        self.tabs
+            .get(key)
+            .export(path, passphrase)
     }
 
-    pub fn add_new_protected_tab(&mut self) -> Result<(), AppError> {
-        let (name, password) = self.new_tab.take();
-        self.tabs.add(name, Some(password))
+    /// Imports age file as a new protected tab named after the file
+    pub fn import_tab(&mut self, path: &Path) -> Result<Secret<String>, AppError> {
+        let (passphrase, password) = self.password_form.take()?;
+        if passphrase.expose().is_empty() {
+            return Err(AppError::EmptyPassword);
+        }
+        self.config.check_password(password.expose())?;
+        let params = self.config.kdf();
+
+        let name = path
+            .file_stem().ok_or(AppError::internal("Not a file"))?
+            .to_str().ok_or(AppError::internal("Invalid file name"))?
+            .to_string();
+        let text = export::read(path, passphrase)?;
+
+        let mut tab_key = Key::generate()?;
+        tab_key.set_password(&Factors::password(password), &params)?;
+        let code = tab_key.new_recovery_code(&params)?;
+
+        let key = self.tabs.add(name, Some(tab_key), false)?;
+        self.tabs
+            .get_mut(key)
+            .set_text(text)?;
+        Ok(code)
     }
 
     pub fn save(&mut self) -> Result<(), AppError> {
-        if self.config.auto_backup {
+        if self.config.auto_backup && !self.backup_passphrase_missing() {
             self.made_backup()?;
         } else {
-            self.tabs.save()?;
+            self.tabs.save(&self.config.history_retention())?;
         }
 
         Ok(())
     }
 
+    pub fn autosave(&mut self) -> Result<(), AppError> {
+        self.tabs.autosave()
+    }
+
     pub fn made_backup(&mut self) -> Result<(), AppError> {
-        self.tabs.save()?;
+        self.tabs.save(&self.config.history_retention())?;
+        self.upload_backup()
+    }
 
+    /// Uploads docs as they are on disk, unsaved text is not included
+    pub fn upload_backup(&self) -> Result<(), AppError> {
         let mut backup = Backup::google_drive()?;
         let names = (&self.config.backup_folder, &self.config.backup_file);
         let docs_path = state::docs_path()?;
         let docs = state::docs(&docs_path)?;
-        backup.backup(names, &docs)?;
+        let passphrase = if self.config.encrypt_backup {
+            Some(self.backup_passphrase().ok_or(AppError::BackupPassphraseRequired)?)
+        } else {
+            None
+        };
+        backup.backup(names, &docs_path, &docs, passphrase.as_ref())?;
 
         Ok(())
     }
 
     pub fn restore_backup(&mut self) -> Result<(), AppError> {
-        self.tabs.save()?;
+        self.tabs.save(&self.config.history_retention())?;
+        self.download_backup()
+    }
 
+    /// Replaces docs with backup, unsaved text is not saved first
+    pub fn download_backup(&mut self) -> Result<(), AppError> {
         let mut backup = Backup::google_drive()?;
         let names = (&self.config.backup_folder, &self.config.backup_file);
         let docs_path = state::docs_path()?;
-        backup.restore(names, &docs_path)?;
+        backup.restore(names, &docs_path, self.backup_passphrase().as_ref())?;
 
         self.tabs.reload()?;
         Ok(())
     }
+
+    /// Encrypted auto backup is skipped until passphrase is entered in this session
+    pub fn backup_passphrase_missing(&self) -> bool {
+        self.config.encrypt_backup && self.backup_passphrase().is_none()
+    }
+
+    fn backup_passphrase(&self) -> Option<Secret<String>> {
+        let passphrase = self.backup_passphrase.value();
+        if passphrase.is_empty() {
+            None
+        } else {
+            Some(Secret::new(passphrase.to_string()))
+        }
+    }
 }
\ No newline at end of file
@@ -1,35 +1,116 @@
 use druid::Data;
 use std::fs::File;
 use std::path::PathBuf;
+use std::time::Duration;
 use serde::{Serialize, Deserialize};
-use crate::{AppError, exe_directory};
+use crate::{AppError, hash, paths, strength};
+use crate::hash::Argon2Params;
+use crate::state::history::Retention;
+
+const DEFAULT_UNLOCK_TIME_MS : u64 = 500;
+const DEFAULT_MIN_PASSWORD_LENGTH : u64 = 8;
+const DEFAULT_MIN_PASSWORD_SCORE : u8 = 2;
+const DEFAULT_AUTO_PURGE_DAYS : u64 = 30;
+const DEFAULT_HISTORY_VERSIONS : u64 = 20;
+const DEFAULT_HISTORY_DAYS : u64 = 90;
+const DEFAULT_AUTOSAVE_SECONDS : u64 = 30;
 
 #[derive(Serialize, Deserialize, Debug, Clone, Data)]
 pub struct Config {
     pub auto_backup: bool,
     pub backup_folder: String,
     pub backup_file: String,
+    #[serde(default)]
+    pub encrypt_backup: bool,
+    #[serde(default = "Config::default_unlock_time_ms")]
+    pub unlock_time_ms: u64,
+    #[serde(default)]
+    pub kdf: Option<Argon2Params>,
+    #[serde(default)]
+    pub auto_lock_minutes: u64,
+    #[serde(default)]
+    pub lock_on_minimize: bool,
+    #[serde(default = "Config::default_min_password_length")]
+    pub min_password_length: u64,
+    // Estimated strength, see strength::score
+    #[serde(default = "Config::default_min_password_score")]
+    pub min_password_score: u8,
+    // Days removed notes are kept in trash, 0 - forever
+    #[serde(default = "Config::default_auto_purge_days")]
+    pub auto_purge_days: u64,
+    // Versions kept for each note, 0 - history is off
+    #[serde(default = "Config::default_history_versions")]
+    pub history_versions: u64,
+    // 0 - versions are not removed by age
+    #[serde(default = "Config::default_history_days")]
+    pub history_days: u64,
+    // 0 - notes are saved only on Ctrl+S and exit
+    #[serde(default = "Config::default_autosave_seconds")]
+    pub autosave_seconds: u64
 }
 
 impl Config {
     pub fn load() -> Result<Self, AppError> {
         let config_path = Config::config_path()?;
-        if config_path.exists() {
+        let mut config = if config_path.exists() {
             let file = File::open(config_path)?;
-            Ok(serde_json::from_reader(file)?)
+            serde_json::from_reader(file)?
         } else {
-            let empty = Config {
+            Config {
                 auto_backup: false,
                 backup_folder: "Backups".to_string(),
-                backup_file: "notepad_backup.zip".to_string()
-            };
-            let file = File::create(config_path)?;
-            serde_json::to_writer_pretty(file, &empty)?;
-            Ok(empty)
+                backup_file: "notepad_backup.zip".to_string(),
+                encrypt_backup: false,
+                unlock_time_ms: DEFAULT_UNLOCK_TIME_MS,
+                kdf: None,
+                auto_lock_minutes: 0,
+                lock_on_minimize: false,
+                min_password_length: DEFAULT_MIN_PASSWORD_LENGTH,
+                min_password_score: DEFAULT_MIN_PASSWORD_SCORE,
+                auto_purge_days: DEFAULT_AUTO_PURGE_DAYS,
+                history_versions: DEFAULT_HISTORY_VERSIONS,
+                history_days: DEFAULT_HISTORY_DAYS,
+                autosave_seconds: DEFAULT_AUTOSAVE_SECONDS
+            }
+        };
+
+        // Parameters edited in config.json are limited the same way as ones read from files
+        config.kdf = config.kdf.map(Argon2Params::clamped);
+        Ok(config)
+    }
+
+    pub fn calibrate(&mut self) -> Result<(), AppError> {
+        let target = Duration::from_millis(self.unlock_time_ms);
+        self.kdf = Some(hash::calibrate(target)?);
+        Ok(())
+    }
+
+    pub fn kdf(&self) -> Argon2Params {
+        self.kdf.unwrap_or_default().clamped()
+    }
+
+    pub fn history_retention(&self) -> Retention {
+        Retention {
+            versions: self.history_versions,
+            days: self.history_days
         }
     }
 
+    pub fn check_password(&self, password: &str) -> Result<(), AppError> {
+        if password.is_empty() {
+            return Err(AppError::EmptyPassword);
+        }
+        let length = password.chars().count() as u64;
+        if length < self.min_password_length || strength::score(password) < self.min_password_score {
+            return Err(AppError::WeakPassword);
+        }
+        Ok(())
+    }
+
     pub fn save(&self) -> Result<(), AppError> {
+        if self.kdf.is_some_and(|params| !params.is_supported()) {
+            return Err(AppError::UnsupportedKdf);
+        }
         let config_path = Config::config_path()?;
         let file = File::create(config_path)?;
         serde_json::to_writer_pretty(file, self)?;
@@ -37,6 +118,34 @@ impl Config {
     }
 
     fn config_path() -> Result<PathBuf, AppError> {
-        Ok(exe_directory()?.join("config.json"))
+        Ok(paths::config_directory()?.join("config.json"))
+    }
+
+    fn default_unlock_time_ms() -> u64 {
+        DEFAULT_UNLOCK_TIME_MS
+    }
+
+    fn default_min_password_length() -> u64 {
+        DEFAULT_MIN_PASSWORD_LENGTH
+    }
+
+    fn default_min_password_score() -> u8 {
+        DEFAULT_MIN_PASSWORD_SCORE
+    }
+
+    fn default_auto_purge_days() -> u64 {
+        DEFAULT_AUTO_PURGE_DAYS
+    }
+
+    fn default_history_versions() -> u64 {
+        DEFAULT_HISTORY_VERSIONS
+    }
+
+    fn default_history_days() -> u64 {
+        DEFAULT_HISTORY_DAYS
+    }
+
+    fn default_autosave_seconds() -> u64 {
+        DEFAULT_AUTOSAVE_SECONDS
     }
 }
\ No newline at end of file
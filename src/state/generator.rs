@@ -0,0 +1,82 @@
+use druid::{Data, Lens};
+use crate::error::AppError;
+use crate::generator;
+use crate::generator::Classes;
+use crate::secret::Secret;
+
+#[derive(Clone, Data, Lens)]
+pub struct Generator {
+    pub passphrase: bool,
+    pub length: u64,
+    pub lowercase: bool,
+    pub uppercase: bool,
+    pub digits: bool,
+    pub symbols: bool,
+    pub words: u64,
+    pub separator: String,
+    value: Secret<String>
+}
+
+impl Generator {
+    pub fn new() -> Self {
+        Generator {
+            passphrase: false,
+            length: 20,
+            lowercase: true,
+            uppercase: true,
+            digits: true,
+            symbols: true,
+            words: 6,
+            separator: "-".to_string(),
+            value: Secret::empty()
+        }
+    }
+
+    pub fn generate(&mut self) -> Result<(), AppError> {
+        if !self.can_generate() {
+            return Err(AppError::internal("Invalid generator settings"));
+        }
+        self.value = if self.passphrase {
+            generator::passphrase(self.words as usize, &self.separator)?
+        } else {
+            generator::password(self.length as usize, self.classes())?
+        };
+        Ok(())
+    }
+
+    pub fn value(&self) -> &str {
+        self.value.expose()
+    }
+
+    pub fn take(&mut self) -> Secret<String> {
+        std::mem::replace(&mut self.value, Secret::empty())
+    }
+
+    pub fn can_generate(&self) -> bool {
+        // Length and words are typed in, they are limited before anything is allocated for them
+        if self.passphrase {
+            (1..=generator::MAX_PASSPHRASE_WORDS as u64).contains(&self.words)
+        } else {
+            let classes = [self.lowercase, self.uppercase, self.digits, self.symbols];
+            let required = classes.iter().filter(|c| **c).count() as u64;
+            required > 0 && (required..=generator::MAX_PASSWORD_LENGTH as u64).contains(&self.length)
+        }
+    }
+
+    pub fn entropy(&self) -> f64 {
+        if self.passphrase {
+            generator::passphrase_entropy(self.words as usize)
+        } else {
+            generator::password_entropy(self.length as usize, self.classes())
+        }
+    }
+
+    fn classes(&self) -> Classes {
+        Classes {
+            lowercase: self.lowercase,
+            uppercase: self.uppercase,
+            digits: self.digits,
+            symbols: self.symbols
+        }
+    }
+}
\ No newline at end of file
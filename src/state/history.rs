@@ -0,0 +1,194 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use druid::Data;
+use similar::{ChangeTag, TextDiff};
+use crate::error::AppError;
+use crate::{gen, paths, state};
+use crate::state::index::Meta;
+
+/// How many versions of each note are kept
+pub struct Retention {
+    // 0 - history is off
+    pub versions: u64,
+    // 0 - versions are kept until count limit
+    pub days: u64
+}
+
+#[derive(Clone, Debug, Data, PartialEq)]
+pub struct Version {
+    // File name in history folder of the note
+    pub id: String,
+    #[data(same_fn = "PartialEq::eq")]
+    pub created: DateTime<Utc>
+}
+
+/// Newest first
+pub fn versions(meta: &Meta) -> Result<Vec<Version>, AppError> {
+    let dir = match &meta.history {
+        Some(id) => paths::history_directory()?.join(id),
+        None => return Ok(Vec::new())
+    };
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut versions = Vec::new();
+    for entry in fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        let created = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.parse::<i64>().ok())
+            .and_then(|ms| Utc.timestamp_millis_opt(ms).single());
+
+        if let (Some(created), Some(id)) = (created, path.file_stem().and_then(|s| s.to_str())) {
+            versions.push(Version {
+                id: id.to_string(),
+                created
+            });
+        }
+    }
+
+    versions.sort_by_key(|version| Reverse(version.created));
+    Ok(versions)
+}
+
+/// Copies saved note file as it is, so versions of protected notes stay encrypted with the note key
+pub fn add(meta: &mut Meta, source: &Path, retention: &Retention) -> Result<(), AppError> {
+    if retention.versions == 0 {
+        return Ok(());
+    }
+
+    // Note is not changed since the last version
+    if let Some(newest) = versions(meta)?.first() {
+        if newest.created >= meta.modified {
+            return Ok(());
+        }
+    }
+
+    if meta.history.is_none() {
+        meta.history = Some(gen::hex_id()?);
+    }
+
+    let created = Utc::now();
+    let path = path(meta, &created.timestamp_millis().to_string())?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(source, path)?;
+
+    prune(meta, retention)
+}
+
+pub fn path(meta: &Meta, id: &str) -> Result<PathBuf, AppError> {
+    let history = meta.history
+        .as_ref()
+        .ok_or(AppError::internal("Note has no history"))?;
+
+    Ok(paths::history_directory()?
+        .join(history)
+        .join(id)
+        .with_extension("txt"))
+}
+
+pub fn remove_all(meta: &Meta) -> Result<(), AppError> {
+    for version in versions(meta)? {
+        remove(meta, &version.id)?;
+    }
+
+    if let Some(id) = &meta.history {
+        let dir = paths::history_directory()?.join(id);
+        if dir.is_dir() {
+            fs::remove_dir(dir)?;
+        }
+    }
+    Ok(())
+}
+
+/// Lines of old text are marked with `-`, lines of new text with `+`
+pub fn diff(old: &str, new: &str) -> String {
+    let mut result = String::new();
+    for change in TextDiff::from_lines(old, new).iter_all_changes() {
+        let sign = match change.tag() {
+            ChangeTag::Delete => '-',
+            ChangeTag::Insert => '+',
+            ChangeTag::Equal => ' '
+        };
+        result.push(sign);
+        result.push_str(change.value());
+        if change.missing_newline() {
+            result.push('\n');
+        }
+    }
+    result
+}
+
+fn prune(meta: &Meta, retention: &Retention) -> Result<(), AppError> {
+    let expired = Utc::now() - Duration::days(retention.days as i64);
+    for (index, version) in versions(meta)?.iter().enumerate() {
+        let old = retention.days != 0 && version.created < expired;
+        if index as u64 >= retention.versions || old {
+            remove(meta, &version.id)?;
+        }
+    }
+    Ok(())
+}
+
+// Versions of clear notes are plain text, so they are wiped as well
+fn remove(meta: &Meta, id: &str) -> Result<(), AppError> {
+    let path = path(meta, id)?;
+    state::wipe(&path)?;
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Each test has its own history folder in shared data folder
+    fn meta_with_versions(ages_in_days: &[i64]) -> Meta {
+        paths::init_for_test();
+        let mut meta = Meta::new(0);
+        meta.history = Some(gen::hex_id().unwrap());
+        for days in ages_in_days {
+            let created = Utc::now() - Duration::days(*days);
+            let path = path(&meta, &created.timestamp_millis().to_string()).unwrap();
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, days.to_string()).unwrap();
+        }
+        meta
+    }
+
+    fn texts(meta: &Meta) -> Vec<String> {
+        versions(meta)
+            .unwrap()
+            .iter()
+            .map(|v| fs::read_to_string(path(meta, &v.id).unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn prune_keeps_newest_versions() {
+        let meta = meta_with_versions(&[3, 1, 4, 2]);
+        prune(&meta, &Retention { versions: 2, days: 0 }).unwrap();
+        assert_eq!(texts(&meta), vec!["1", "2"]);
+    }
+
+    #[test]
+    fn prune_removes_expired_versions() {
+        let meta = meta_with_versions(&[1, 10, 5]);
+        prune(&meta, &Retention { versions: 10, days: 7 }).unwrap();
+        assert_eq!(texts(&meta), vec!["1", "5"]);
+    }
+
+    #[test]
+    fn remove_all_removes_history_folder() {
+        let meta = meta_with_versions(&[1, 2]);
+        remove_all(&meta).unwrap();
+        assert!(versions(&meta).unwrap().is_empty());
+        assert!(!paths::history_directory().unwrap().join(meta.history.unwrap()).exists());
+    }
+}
\ No newline at end of file
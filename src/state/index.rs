@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::PathBuf;
+use chrono::{DateTime, Utc};
+use druid::Data;
+use serde::{Serialize, Deserialize};
+use crate::error::AppError;
+use crate::state;
+
+// Kept in docs, so it is backed up and restored together with notes
+const FILE_NAME : &str = "index.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelColor {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple
+}
+
+impl LabelColor {
+    pub const ALL : [LabelColor; 6] = [
+        LabelColor::Red,
+        LabelColor::Orange,
+        LabelColor::Yellow,
+        LabelColor::Green,
+        LabelColor::Blue,
+        LabelColor::Purple
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LabelColor::Red => "Red",
+            LabelColor::Orange => "Orange",
+            LabelColor::Yellow => "Yellow",
+            LabelColor::Green => "Green",
+            LabelColor::Blue => "Blue",
+            LabelColor::Purple => "Purple"
+        }
+    }
+}
+
+/// Per note data that is not stored in the note file itself
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Data)]
+pub struct Meta {
+    pub position: u64,
+    #[data(same_fn = "PartialEq::eq")]
+    pub created: DateTime<Utc>,
+    #[data(same_fn = "PartialEq::eq")]
+    pub modified: DateTime<Utc>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    #[data(same_fn = "PartialEq::eq")]
+    pub color: Option<LabelColor>,
+    // Name of folder with versions, it is kept when note is renamed or moved
+    #[serde(default)]
+    pub history: Option<String>
+}
+
+impl Meta {
+    pub fn new(position: u64) -> Self {
+        let now = Utc::now();
+        Meta {
+            position,
+            created: now,
+            modified: now,
+            pinned: false,
+            color: None,
+            history: None
+        }
+    }
+}
+
+/// Metadata of notes by file name in docs
+#[derive(Serialize, Deserialize, Default)]
+pub struct Index {
+    notes: BTreeMap<String, Meta>
+}
+
+impl Index {
+    pub fn load() -> Result<Self, AppError> {
+        let path = Index::path()?;
+        if !path.exists() {
+            return Ok(Index::default());
+        }
+
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    pub fn save(&self) -> Result<(), AppError> {
+        let file = File::create(Index::path()?)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+
+    pub fn get(&self, file: &str) -> Option<&Meta> {
+        self.notes.get(file)
+    }
+
+    pub fn insert(&mut self, file: String, meta: Meta) {
+        self.notes.insert(file, meta);
+    }
+
+    /// Marks note as modified now, unknown note is added after the others
+    pub fn touch(&mut self, file: &str) {
+        let position = self.next_position();
+        self.notes
+            .entry(file.to_string())
+            .or_insert_with(|| Meta::new(position))
+            .modified = Utc::now();
+    }
+
+    pub fn next_position(&self) -> u64 {
+        self.notes
+            .values()
+            .map(|m| m.position + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn path() -> Result<PathBuf, AppError> {
+        Ok(state::docs_path()?.join(FILE_NAME))
+    }
+}
\ No newline at end of file
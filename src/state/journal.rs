@@ -0,0 +1,294 @@
+use std::fs;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use chrono::{DateTime, Utc};
+use druid::Data;
+use serde::{Serialize, Deserialize};
+use crate::error::AppError;
+use crate::{gen, paths, state};
+use crate::state::tab::Tab;
+
+const JOURNAL_DIR : &str = "journal";
+// Journal of previous run, it is kept until every entry is recovered or discarded
+const RECOVERY_DIR : &str = "recovery";
+const INDEX_FILE_NAME : &str = "index.json";
+
+// Writes are numbered when tabs are taken, so write that gets the lock after a newer one is skipped
+static STARTED : AtomicU64 = AtomicU64::new(0);
+static WRITTEN : Mutex<u64> = Mutex::new(0);
+
+/// Unsaved text of a note, written in the same format as the note, so protected text stays encrypted
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Data)]
+pub struct Entry {
+    pub id: String,
+    // Path of note in docs
+    pub file: String,
+    pub label: String,
+    #[data(same_fn = "PartialEq::eq")]
+    pub modified: DateTime<Utc>
+}
+
+/// Writes unsaved tabs, journal is removed when there is nothing unsaved, so it is left only by unclean shutdown
+pub fn write<'a>(tabs: impl Iterator<Item = &'a Tab>) -> Result<(), AppError> {
+    let unsaved: Vec<&Tab> = tabs
+        .filter(|t| t.dirty && t.opened())
+        .collect();
+    write_numbered(STARTED.fetch_add(1, Ordering::SeqCst) + 1, &unsaved)
+}
+
+/// Unsaved tabs are copied and written by worker thread, so UI is not blocked by encryption and disk
+pub fn write_in_background<'a>(tabs: impl Iterator<Item = &'a Tab>, done: impl FnOnce(Result<(), AppError>) + Send + 'static) {
+    let unsaved: Vec<Tab> = tabs
+        .filter(|t| t.dirty && t.opened())
+        .cloned()
+        .collect();
+    let number = STARTED.fetch_add(1, Ordering::SeqCst) + 1;
+    thread::spawn(move || {
+        let unsaved: Vec<&Tab> = unsaved.iter().collect();
+        done(write_numbered(number, &unsaved))
+    });
+}
+
+fn write_numbered(number: u64, unsaved: &[&Tab]) -> Result<(), AppError> {
+    let mut written = WRITTEN
+        .lock()
+        .map_err(|_| AppError::internal("Journal lock is poisoned"))?;
+    if *written > number {
+        return Ok(());
+    }
+    *written = number;
+
+    let dir = directory(JOURNAL_DIR)?;
+    if unsaved.is_empty() {
+        if dir.exists() {
+            remove_dir(&dir)?;
+        }
+        return Ok(());
+    }
+
+    fs::create_dir_all(&dir)?;
+    let mut entries = Vec::new();
+    for tab in unsaved {
+        let id = gen::hex_id()?;
+        tab.save_copy(&dir.join(&id).with_extension("txt"))?;
+        entries.push(Entry {
+            id,
+            file: tab.file.clone(),
+            label: tab.public_label().to_string(),
+            modified: tab.meta.modified
+        });
+    }
+
+    write_index(&dir, &entries)?;
+
+    for entry in fs::read_dir(&dir)?.flatten() {
+        let path = entry.path();
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+        if path.extension() == Some("txt".as_ref()) && !entries.iter().any(|e| e.id == stem) {
+            remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Takes journal left by previous run, it is moved aside, so new journal doesn't overwrite it.
+/// Entries of earlier runs that are not recovered yet are kept.
+pub fn recover() -> Result<Vec<Entry>, AppError> {
+    let journal = directory(JOURNAL_DIR)?;
+    let recovery = directory(RECOVERY_DIR)?;
+    if journal.join(INDEX_FILE_NAME).exists() {
+        if recovery.join(INDEX_FILE_NAME).exists() {
+            let mut entries = read_index(&recovery)?;
+            for entry in read_index(&journal)? {
+                let file = Path::new(&entry.id).with_extension("txt");
+                fs::rename(journal.join(&file), recovery.join(&file))?;
+                entries.push(entry);
+            }
+            write_index(&recovery, &entries)?;
+            remove_dir(&journal)?;
+        } else {
+            if recovery.exists() {
+                remove_dir(&recovery)?;
+            }
+            fs::rename(&journal, &recovery)?;
+        }
+    }
+
+    if !recovery.join(INDEX_FILE_NAME).exists() {
+        return Ok(Vec::new());
+    }
+    read_index(&recovery)
+}
+
+pub fn recovery_path(id: &str) -> Result<PathBuf, AppError> {
+    Ok(directory(RECOVERY_DIR)?
+        .join(id)
+        .with_extension("txt"))
+}
+
+/// Rewrites recovery index with entries that are left, recovery is removed with the last one
+pub fn discard(id: &str, entries: &[Entry]) -> Result<(), AppError> {
+    let recovery = directory(RECOVERY_DIR)?;
+    let left: Vec<&Entry> = entries
+        .iter()
+        .filter(|e| e.id != id)
+        .collect();
+
+    if left.is_empty() {
+        return remove_dir(&recovery);
+    }
+
+    write_index(&recovery, &left)?;
+    let path = recovery_path(id)?;
+    if path.exists() {
+        remove_file(&path)?;
+    }
+    Ok(())
+}
+
+// Copies of clear notes are plain text, so they are wiped as well
+fn remove_file(path: &Path) -> Result<(), AppError> {
+    state::wipe(path)?;
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+fn remove_dir(dir: &Path) -> Result<(), AppError> {
+    for entry in fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        if path.is_file() {
+            remove_file(&path)?;
+        }
+    }
+    fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+fn read_index(dir: &Path) -> Result<Vec<Entry>, AppError> {
+    Ok(serde_json::from_reader(File::open(dir.join(INDEX_FILE_NAME))?)?)
+}
+
+// Index is replaced at once, so it never points to missing or half written entries
+fn write_index<E: Serialize>(dir: &Path, entries: &[E]) -> Result<(), AppError> {
+    let saving = dir.join(INDEX_FILE_NAME).with_extension("saving.json");
+    serde_json::to_writer_pretty(File::create(&saving)?, entries)?;
+    fs::rename(saving, dir.join(INDEX_FILE_NAME))?;
+    Ok(())
+}
+
+fn directory(name: &str) -> Result<PathBuf, AppError> {
+    Ok(paths::data_directory()?.join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::tab_content::TabContent;
+
+    fn unsaved_tab(id: u64, name: &str, text: &str) -> Tab {
+        fs::create_dir_all(paths::docs_directory().unwrap()).unwrap();
+        let mut tab = Tab::new(id, "", format!("journal {} {}", name, id), None, false).unwrap();
+        tab.content = TabContent::Clear { text: text.to_string() };
+        tab.touch();
+        tab
+    }
+
+    // Journal and recovery folders are shared, so tests take turns and start without them
+    fn fresh() -> std::sync::MutexGuard<'static, ()> {
+        static LOCK : Mutex<()> = Mutex::new(());
+        let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        paths::init_for_test();
+        for name in [JOURNAL_DIR, RECOVERY_DIR] {
+            let dir = directory(name).unwrap();
+            if dir.exists() {
+                fs::remove_dir_all(dir).unwrap();
+            }
+        }
+        lock
+    }
+
+    fn recovered_text(tab: &Tab, entry: &Entry) -> String {
+        tab.read_copy(&recovery_path(&entry.id).unwrap())
+            .unwrap()
+            .expose()
+            .clone()
+    }
+
+    #[test]
+    fn write_keeps_only_unsaved_tabs() {
+        let _lock = fresh();
+        let first = unsaved_tab(1, "first", "first text");
+        let mut second = unsaved_tab(2, "second", "second text");
+        second.dirty = false;
+
+        write([&first, &second].into_iter()).unwrap();
+        let entries = recover().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].file, first.file);
+        assert_eq!(entries[0].label, first.public_label());
+        assert_eq!(recovered_text(&first, &entries[0]), "first text");
+    }
+
+    #[test]
+    fn recover_merges_next_journal() {
+        let _lock = fresh();
+        let first = unsaved_tab(3, "first", "first text");
+        let second = unsaved_tab(4, "second", "second text");
+
+        write([&first].into_iter()).unwrap();
+        recover().unwrap();
+        write([&second].into_iter()).unwrap();
+        let entries = recover().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(recovered_text(&first, &entries[0]), "first text");
+        assert_eq!(recovered_text(&second, &entries[1]), "second text");
+        assert!(!directory(JOURNAL_DIR).unwrap().exists());
+    }
+
+    #[test]
+    fn discard_removes_entry_and_copy() {
+        let _lock = fresh();
+        let first = unsaved_tab(5, "first", "first text");
+        let second = unsaved_tab(6, "second", "second text");
+
+        write([&first, &second].into_iter()).unwrap();
+        let entries = recover().unwrap();
+        discard(&entries[0].id, &entries).unwrap();
+        assert!(!recovery_path(&entries[0].id).unwrap().exists());
+        let entries = recover().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].file, second.file);
+
+        discard(&entries[0].id, &entries).unwrap();
+        assert!(recover().unwrap().is_empty());
+        assert!(!directory(RECOVERY_DIR).unwrap().exists());
+    }
+
+    #[test]
+    fn nothing_unsaved_removes_journal() {
+        let _lock = fresh();
+        let mut tab = unsaved_tab(7, "tab", "text");
+
+        write([&tab].into_iter()).unwrap();
+        assert!(directory(JOURNAL_DIR).unwrap().exists());
+        tab.dirty = false;
+        write([&tab].into_iter()).unwrap();
+        assert!(!directory(JOURNAL_DIR).unwrap().exists());
+    }
+
+    #[test]
+    fn background_write_reports_result() {
+        let _lock = fresh();
+        let tab = unsaved_tab(8, "tab", "background text");
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        write_in_background([&tab].into_iter(), move |result| sender.send(result).unwrap());
+        receiver.recv().unwrap().unwrap();
+        let entries = recover().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(recovered_text(&tab, &entries[0]), "background text");
+    }
+}
\ No newline at end of file
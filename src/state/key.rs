@@ -0,0 +1,221 @@
+use std::fmt::{Debug, Formatter};
+use druid::Data;
+use crate::error::AppError;
+use crate::format::{Kdf, Slot, WrappedKey};
+use crate::hash::Argon2Params;
+use crate::recovery;
+use crate::secret::Secret;
+use crate::state::vault::Vault;
+
+/// Everything user provides to unlock a tab: password and optional key file hash
+pub struct Factors {
+    pub password: Secret<String>,
+    pub key_file: Option<Secret<[u8; 32]>>
+}
+
+impl Factors {
+    pub fn new(password: Secret<String>, key_file: Option<Secret<[u8; 32]>>) -> Self {
+        Factors {
+            password,
+            key_file
+        }
+    }
+
+    pub fn password(password: Secret<String>) -> Self {
+        Factors::new(password, None)
+    }
+
+    fn key_file(&self) -> Option<&[u8]> {
+        self.key_file.as_ref().map(|k| &k.expose()[..])
+    }
+}
+
+/// Random data key of a tab with slots it can be unwrapped by.
+/// Slots are kept wrapped, so saving never derives keys again.
+#[derive(Clone, PartialEq)]
+pub struct Key {
+    data: Secret<[u8; 32]>,
+    slots: Vec<Slot>,
+    key_file: Option<Secret<[u8; 32]>>
+}
+
+impl Key {
+    pub fn generate() -> Result<Self, AppError> {
+        let mut data = Secret::zeroed();
+        data.edit(|d| getrandom::getrandom(d))?;
+        Ok(Key {
+            data,
+            slots: Vec::new(),
+            key_file: None
+        })
+    }
+
+    /// Tries every slot, first one that fits gives the key.
+    /// Vault slots go first, they don't need key derivation.
+    pub fn open(slots: &[Slot], factors: &Factors, vault: &Vault) -> Result<Self, AppError> {
+        let is_vault = |s: &&Slot| matches!(s, Slot::Vault { .. });
+        let ordered = slots.iter()
+            .filter(is_vault)
+            .chain(slots.iter().filter(|s| !is_vault(s)));
+
+        let mut error = AppError::InvalidPassword;
+        for slot in ordered {
+            let data = match slot {
+                Slot::Vault { id, wrapped } => match vault.master(id) {
+                    Some(master) => wrapped.unwrap(master.expose()),
+                    None => continue
+                },
+                Slot::Recovery { kdf, wrapped } => match recovery::normalize(factors.password.expose()) {
+                    Some(code) => wrapped.unwrap(kdf.derive(code.expose(), None)?.expose()),
+                    None => continue
+                },
+                _ if slot.requires_key_file() && factors.key_file.is_none() => {
+                    error = AppError::KeyFileRequired;
+                    continue
+                },
+                Slot::Password { kdf, key_file, wrapped } => {
+                    let key_file = if *key_file { factors.key_file() } else { None };
+                    wrapped.unwrap(kdf.derive(factors.password.expose(), key_file)?.expose())
+                },
+                // Can't be checked until data is decrypted
+                Slot::Direct { kdf, key_file } => {
+                    let key_file = if *key_file { factors.key_file() } else { None };
+                    kdf.derive(factors.password.expose(), key_file)
+                }
+            };
+
+            match data {
+                Ok(data) => return Ok(Key {
+                    data,
+                    slots: slots.to_vec(),
+                    key_file: factors.key_file.clone()
+                }),
+                Err(AppError::InvalidPassword) => continue,
+                Err(e) => return Err(e)
+            }
+        }
+        Err(error)
+    }
+
+    /// Version 1 files have no data key, they get one on next save
+    pub fn is_outdated(&self) -> bool {
+        self.slots.iter().any(|s| matches!(s, Slot::Direct { .. }))
+    }
+
+    pub fn set_password(&mut self, factors: &Factors, params: &Argon2Params) -> Result<(), AppError> {
+        let kdf = Kdf::argon2id(*params)?;
+        let kek = kdf.derive(factors.password.expose(), factors.key_file())?;
+        let slot = Slot::Password {
+            kdf,
+            key_file: factors.key_file.is_some(),
+            wrapped: WrappedKey::wrap(kek.expose(), &self.data)?
+        };
+
+        self.slots.retain(|s| !matches!(s, Slot::Password { .. }));
+        self.slots.push(slot);
+        self.key_file = factors.key_file.clone();
+        Ok(())
+    }
+
+    /// Password is replaced, key file stays the same.
+    /// Key opened by vault or recovery code has no key file hash, so it can't keep the key file factor.
+    pub fn change_password(&mut self, password: Secret<String>, params: &Argon2Params) -> Result<(), AppError> {
+        if self.key_file.is_none() && self.slots.iter().any(Slot::requires_key_file) {
+            return Err(AppError::KeyFileRequired);
+        }
+        let factors = Factors::new(password, self.key_file.clone());
+        self.set_password(&factors, params)
+    }
+
+    /// Replaces recovery slot, so previous code stops working
+    pub fn new_recovery_code(&mut self, params: &Argon2Params) -> Result<Secret<String>, AppError> {
+        let code = recovery::generate()?;
+        let normalized = recovery::normalize(code.expose()).ok_or(AppError::internal("Invalid recovery code"))?;
+
+        let kdf = Kdf::argon2id(*params)?;
+        let kek = kdf.derive(normalized.expose(), None)?;
+        let slot = Slot::Recovery {
+            kdf,
+            wrapped: WrappedKey::wrap(kek.expose(), &self.data)?
+        };
+
+        self.slots.retain(|s| !matches!(s, Slot::Recovery { .. }));
+        self.slots.push(slot);
+        Ok(code)
+    }
+
+    pub fn add_to_vault(&mut self, vault: &Vault) -> Result<(), AppError> {
+        let slot = vault.slot(&self.data)?;
+        self.slots.retain(|s| !matches!(s, Slot::Vault { .. }));
+        self.slots.push(slot);
+        Ok(())
+    }
+
+    pub fn slots(&self) -> &[Slot] {
+        &self.slots
+    }
+
+    pub fn has_key_file(&self) -> bool {
+        self.slots.iter().any(|s| s.requires_key_file())
+    }
+
+    pub fn in_vault(&self) -> bool {
+        self.slots.iter().any(|s| matches!(s, Slot::Vault { .. }))
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        self.data.expose()
+    }
+
+    /// Checks tab password. Recovery code and master password of the vault are accepted too,
+    /// so tab opened with them can get new password.
+    pub fn verify(&self, password: &str, vault: &Vault) -> Result<(), AppError> {
+        let mut error = AppError::InvalidPassword;
+        for slot in &self.slots {
+            let data = match slot {
+                Slot::Password { key_file: true, .. } if self.key_file.is_none() => {
+                    // Opened without key file, through vault or recovery code
+                    error = AppError::KeyFileRequired;
+                    continue
+                },
+                Slot::Password { kdf, key_file, wrapped } => {
+                    let key_file = if *key_file { self.key_file.as_ref().map(|k| &k.expose()[..]) } else { None };
+                    wrapped.unwrap(kdf.derive(password, key_file)?.expose())
+                },
+                Slot::Recovery { kdf, wrapped } => match recovery::normalize(password) {
+                    Some(code) => wrapped.unwrap(kdf.derive(code.expose(), None)?.expose()),
+                    None => continue
+                },
+                Slot::Vault { .. } if !vault.exists() => continue,
+                Slot::Vault { .. } => match vault.verify(password) {
+                    Ok(_) => return Ok(()),
+                    Err(AppError::InvalidPassword) => continue,
+                    Err(e) => return Err(e)
+                },
+                Slot::Direct { .. } => continue
+            };
+
+            match data {
+                Ok(data) if crypto::util::fixed_time_eq(data.expose(), self.data.expose()) => return Ok(()),
+                Ok(_) | Err(AppError::InvalidPassword) => continue,
+                Err(e) => return Err(e)
+            }
+        }
+        Err(error)
+    }
+}
+
+impl Data for Key {
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl Debug for Key {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Key")
+            .field("slots", &self.slots.len())
+            .field("key_file", &self.key_file.is_some())
+            .finish_non_exhaustive()
+    }
+}
\ No newline at end of file
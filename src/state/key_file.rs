@@ -0,0 +1,69 @@
+use std::path::Path;
+use druid::Data;
+use crate::error::AppError;
+use crate::hash;
+use crate::secret::Secret;
+
+/// Key file chosen in a picker. `selecting` marks the picker that opened file dialog,
+/// because dialog result is sent to the whole window.
+#[derive(Clone, Data, Debug, PartialEq)]
+pub struct KeyFile {
+    path: String,
+    selecting: bool
+}
+
+impl KeyFile {
+    pub fn new() -> Self {
+        KeyFile {
+            path: String::new(),
+            selecting: false
+        }
+    }
+
+    pub fn name(&self) -> String {
+        if self.path.is_empty() {
+            return "No key file".to_string();
+        }
+
+        Path::new(&self.path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.clone())
+    }
+
+    pub fn selecting(&self) -> bool {
+        self.selecting
+    }
+
+    pub fn begin_selection(&mut self) {
+        self.selecting = true;
+    }
+
+    pub fn select(&mut self, path: &Path) {
+        self.path = path.to_string_lossy().to_string();
+        self.selecting = false;
+    }
+
+    pub fn cancel_selection(&mut self) {
+        self.selecting = false;
+    }
+
+    pub fn clear(&mut self) {
+        self.path.clear();
+        self.selecting = false;
+    }
+
+    pub fn hash(&self) -> Result<Option<Secret<[u8; 32]>>, AppError> {
+        if self.path.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(hash::key_file(Path::new(&self.path))?))
+    }
+
+    pub fn take(&mut self) -> Self {
+        let key_file = self.clone();
+        self.clear();
+        key_file
+    }
+}
\ No newline at end of file
@@ -1,7 +1,9 @@
-use std::fs;
+use std::{fs, io};
+use std::fs::OpenOptions;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use crate::error::AppError;
-use crate::exe_directory;
+use crate::paths;
 
 pub mod app_state;
 pub mod tabs;
@@ -9,20 +11,46 @@ pub mod tab;
 pub mod new_tab;
 pub mod tab_content;
 pub mod config;
-
-const DIR : &str = "./docs";
+pub mod key;
+pub mod key_file;
+pub mod password_form;
+pub mod vault;
+pub mod generator;
+pub mod index;
+pub mod trash;
+pub mod history;
+pub mod journal;
 
 fn docs_path() -> Result<PathBuf, AppError> {
-    Ok(exe_directory()?.join(DIR))
+    paths::docs_directory()
 }
 
+// Files and folders of docs with all nested ones, folders go before their content
 fn docs(path: &Path) -> Result<Vec<PathBuf>, AppError> {
     let mut docs = Vec::new();
     for entry_result in fs::read_dir(path)? {
         if let Ok(entry) = entry_result {
-            docs.push(entry.path());
+            let path = entry.path();
+            if path.is_dir() {
+                docs.push(path.clone());
+                docs.append(&mut self::docs(&path)?);
+            } else {
+                docs.push(path);
+            }
         }
     }
 
     Ok(docs)
+}
+
+// Overwrites file content with zeros, so it is not left on disk after removal
+fn wipe(path: &Path) -> Result<(), AppError> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(path)?;
+
+    let len = file.metadata()?.len();
+    io::copy(&mut io::repeat(0).take(len), &mut file)?;
+    file.sync_all()?;
+    Ok(())
 }
\ No newline at end of file
@@ -1,27 +1,44 @@
 use druid::Data;
+use crate::secret::Secret;
+use crate::state::key_file::KeyFile;
 use crate::ui::password_text::PasswordText;
 
 #[derive(Clone, Data)]
 pub struct NewTab {
     pub name: String,
     pub password: PasswordText,
+    pub repeat: PasswordText,
+    pub key_file: KeyFile,
+    pub vault: bool,
+    pub hide_name: bool
 }
 
 impl NewTab {
     pub fn new() -> Self {
         NewTab {
             name: String::new(),
-            password: PasswordText::new()
+            password: PasswordText::new(),
+            repeat: PasswordText::new(),
+            key_file: KeyFile::new(),
+            vault: false,
+            hide_name: false
         }
     }
 
-    pub fn take(&mut self) -> (String, String) {
+    pub fn show_password(&mut self, shown: bool) {
+        self.password.set_shown(shown);
+        self.repeat.set_shown(shown);
+    }
+
+    pub fn take(&mut self) -> (String, Secret<String>, KeyFile) {
         let name = self.name.clone();
-        let password = self.password.value().to_string();
+        let password = self.password.take();
+        self.repeat.clear();
+        self.repeat.set_shown(false);
+        let key_file = self.key_file.take();
 
         self.name.clear();
-        self.password.clear();
 
-        (name, password)
+        (name, password, key_file)
     }
 }
\ No newline at end of file
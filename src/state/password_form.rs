@@ -0,0 +1,35 @@
+use druid::Data;
+use crate::error::AppError;
+use crate::secret::Secret;
+use crate::ui::password_text::PasswordText;
+
+#[derive(Clone, Data)]
+pub struct PasswordForm {
+    pub current: PasswordText,
+    pub password: PasswordText,
+    pub repeat: PasswordText
+}
+
+impl PasswordForm {
+    pub fn new() -> Self {
+        PasswordForm {
+            current: PasswordText::new(),
+            password: PasswordText::new(),
+            repeat: PasswordText::new()
+        }
+    }
+
+    pub fn take(&mut self) -> Result<(Secret<String>, Secret<String>), AppError> {
+        let matches = self.password.value() == self.repeat.value();
+        let current = self.current.take();
+        let password = self.password.take();
+
+        self.repeat.clear();
+
+        if matches {
+            Ok((current, password))
+        } else {
+            Err(AppError::PasswordsMismatch)
+        }
+    }
+}
\ No newline at end of file
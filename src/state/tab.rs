@@ -1,61 +1,384 @@
 use std::fs;
 use std::fs::File;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use chrono::Utc;
 use druid::{Data, Lens};
 use crate::error::AppError;
+use crate::{export, gen, paths};
+use crate::hash::Argon2Params;
+use crate::secret::Secret;
 use crate::state;
+use crate::state::history::{self, Retention, Version};
+use crate::state::index::Meta;
+use crate::state::key::{Factors, Key};
 use crate::state::tab_content::TabContent;
+use crate::state::vault::Vault;
 
 #[derive(Clone, Debug, Data, Lens, PartialEq)]
 pub struct Tab {
     pub id: u64,
+    // Empty while tab with hidden name is locked
     pub name: String,
-    pub content: TabContent
+    // Path in docs with `/` separators and without extension, file name is random when name is hidden
+    pub file: String,
+    pub content: TabContent,
+    pub meta: Meta,
+    // Changed since the last save
+    pub dirty: bool,
+    // Value of dirty when recovery journal was written
+    pub journaled: bool
 }
 
+const LOCKED_LABEL : &str = "Locked note";
+
 impl Tab {
-    pub fn new(id: u64, name: String, password: Option<String>) -> Result<Self, AppError> {
-        let path = Tab::path(&name)?;
+    pub fn new(id: u64, folder: &str, name: String, key: Option<Key>, hide_name: bool) -> Result<Self, AppError> {
+        let (file, title) = if hide_name {
+            (Tab::join(folder, &gen::hex_id()?), Some(name.clone()))
+        } else {
+            (Tab::join(folder, &name), None)
+        };
+
+        let path = Tab::path(&file)?;
         if path.exists() {
             return Err(AppError::FileAlreadyExist);
         }
 
-        File::create(path)?;
-
-        Ok(Tab {
+        let content = TabContent::new(key, title)?;
+        let tab = Tab {
             id,
             name,
-            content: TabContent::new(password)?
-        })
+            file,
+            content,
+            meta: Meta::new(0),
+            dirty: false,
+            journaled: false
+        };
+        // Protected file is written right away, so it is never left on disk as a clear one
+        tab.save()?;
+        Ok(tab)
     }
 
-    pub fn load(id: u64, name: impl AsRef<str>) -> Result<Self, AppError> {
-        let path = Tab::path(&name)?;
-        let mut file = File::open(path)?;
+    /// Metadata is not stored in the file, it is taken from index by caller
+    pub fn load(id: u64, file: impl AsRef<str>) -> Result<Self, AppError> {
+        let path = Tab::path(&file)?;
+        let content = TabContent::read(&mut File::open(path)?)?;
+        let name = if content.hidden_name() { String::new() } else { Tab::file_name(file.as_ref()).to_string() };
         Ok(Tab {
             id,
-            name: name.as_ref().to_string(),
-            content: TabContent::read(&mut file)?
+            name,
+            file: file.as_ref().to_string(),
+            content,
+            meta: Meta::new(0),
+            dirty: false,
+            journaled: false
         })
     }
 
     pub fn update(&mut self, another: &Tab) {
+        self.name = another.name.clone();
         self.content = another.content.clone();
+        self.meta = another.meta.clone();
+        self.dirty = false;
     }
 
-    pub fn open(&mut self) -> Result<(), AppError> {
-        let path = Tab::path(&self.name)?;
+    pub fn open(&mut self, params: &Argon2Params, vault: &Vault) -> Result<(), AppError> {
+        let path = Tab::path(&self.file)?;
         let mut file = File::open(path)?;
-        self.content = self.content.open(&mut file)?;
+        let outdated = TabContent::outdated(&mut file)?;
+        self.content = self.content.open(&mut file, params, vault)?;
+        self.dirty |= outdated;
+        if let Some(title) = self.content.title() {
+            self.name = title.to_string();
+        }
+        Ok(())
+    }
+
+    /// Opens with given factors instead of the password entered into the tab
+    pub fn open_with(&mut self, factors: &Factors, params: &Argon2Params, vault: &Vault) -> Result<(), AppError> {
+        let path = Tab::path(&self.file)?;
+        let mut file = File::open(path)?;
+        let outdated = TabContent::outdated(&mut file)?;
+        self.content = TabContent::unlock(&mut file, factors, params, vault)?;
+        self.dirty |= outdated;
+        if let Some(title) = self.content.title() {
+            self.name = title.to_string();
+        }
+        Ok(())
+    }
+
+    pub fn text(&self) -> Option<&str> {
+        match &self.content {
+            TabContent::Clear { text } => Some(text),
+            TabContent::Opened { text, .. } => Some(text.expose()),
+            TabContent::Closed { .. } => None
+        }
+    }
+
+    pub fn append(&mut self, addition: &str) -> Result<(), AppError> {
+        match &mut self.content {
+            TabContent::Clear { text } => text.push_str(addition),
+            TabContent::Opened { text, .. } => text.edit(|t| t.push_str(addition)),
+            TabContent::Closed { .. } => return Err(AppError::internal("File is not opened"))
+        }
+        self.touch();
+        self.save_changes()
+    }
+
+    pub fn touch(&mut self) {
+        self.meta.modified = Utc::now();
+        self.dirty = true;
+        self.journaled = false;
+    }
+
+    /// Writes file only if it is changed since the last save
+    pub fn save_changes(&mut self) -> Result<(), AppError> {
+        if self.dirty {
+            self.save()?;
+            self.dirty = false;
+        }
+        Ok(())
+    }
+
+    pub fn label(&self) -> &str {
+        if self.name.is_empty() && self.content.hidden_name() {
+            LOCKED_LABEL
+        } else {
+            &self.name
+        }
+    }
+
+    /// Label that may be stored outside of the file, hidden name is never written in clear
+    pub fn public_label(&self) -> &str {
+        if self.hidden_name() {
+            LOCKED_LABEL
+        } else {
+            &self.name
+        }
+    }
+
+    pub fn hidden_name(&self) -> bool {
+        self.content.hidden_name()
+    }
+
+    /// Moves protected file under random name and keeps its name encrypted with the text
+    pub fn hide_name(&mut self) -> Result<(), AppError> {
+        self.content.set_title(Some(self.name.clone()))?;
+        self.move_to(Tab::join(self.folder(), &gen::hex_id()?))
+    }
+
+    pub fn show_name(&mut self) -> Result<(), AppError> {
+        let file = Tab::join(self.folder(), &self.name);
+        if Tab::path(&file)?.exists() {
+            return Err(AppError::FileAlreadyExist);
+        }
+        self.content.set_title(None)?;
+        self.move_to(file)
+    }
+
+    /// Renames file in place, file of tab with hidden name keeps its random name and only title is changed
+    pub fn rename(&mut self, name: String) -> Result<(), AppError> {
+        if self.hidden_name() {
+            if !self.unlocked() {
+                return Err(AppError::internal("File is not opened"));
+            }
+            self.content.set_title(Some(name.clone()))?;
+            self.name = name;
+            return self.save();
+        }
+
+        let file = Tab::join(self.folder(), &name);
+        if file == self.file {
+            return Ok(());
+        }
+
+        let path = Tab::path(&file)?;
+        // Case only rename of the same file is not a collision on case insensitive file systems
+        if path.exists() && !paths::same_file(&path, &Tab::path(&self.file)?) {
+            return Err(AppError::FileAlreadyExist);
+        }
+
+        // Edits are written under old name first, so rename itself is a single atomic operation
+        self.save()?;
+        fs::rename(Tab::path(&self.file)?, path)?;
+        self.file = file;
+        self.name = name;
+        Ok(())
+    }
+
+    /// Folder in docs with `/` separators, empty for notes in docs itself
+    pub fn folder(&self) -> &str {
+        self.file
+            .rsplit_once('/')
+            .map_or("", |(folder, _)| folder)
+    }
+
+    pub fn move_to_folder(&mut self, folder: &str) -> Result<(), AppError> {
+        let file = Tab::join(folder, Tab::file_name(&self.file));
+        if Tab::path(&file)?.exists() {
+            return Err(AppError::FileAlreadyExist);
+        }
+        self.move_to(file)
+    }
+
+    fn move_to(&mut self, file: String) -> Result<(), AppError> {
+        // Locked content is not written by save, so its file is moved as it is
+        if !self.opened() {
+            fs::rename(Tab::path(&self.file)?, Tab::path(&file)?)?;
+            self.file = file;
+            return Ok(());
+        }
+
+        let old_file = std::mem::replace(&mut self.file, file);
+        if let Err(e) = self.save() {
+            self.file = old_file;
+            return Err(e);
+        }
+        fs::remove_file(Tab::path(&old_file)?)?;
+        Ok(())
+    }
+
+    pub fn change_password(&mut self, current: &str, password: Secret<String>, params: &Argon2Params, vault: &Vault) -> Result<(), AppError> {
+        self.content.change_password(current, password, params, vault)?;
+        self.save()
+    }
+
+    pub fn protect(&mut self, key: Key) -> Result<(), AppError> {
+        self.content = self.content.protect(key)?;
+        self.write(true)?;
+        // Clear versions would keep text readable on disk
+        history::remove_all(&self.meta)
+    }
+
+    pub fn unprotect(&mut self, current: &str, vault: &Vault) -> Result<(), AppError> {
+        let content = self.content.unprotect(current, vault)?;
+        if !self.hidden_name() {
+            self.content = content;
+            self.save()?;
+        } else {
+            // Clear file can't hide its name, so it goes back under the real one
+            let file = Tab::join(self.folder(), &self.name);
+            if Tab::path(&file)?.exists() {
+                return Err(AppError::FileAlreadyExist);
+            }
+            self.content = content;
+            self.move_to(file)?;
+        }
+
+        // Encrypted versions can't be opened without the key anymore
+        history::remove_all(&self.meta)
+    }
+
+    pub fn new_recovery_code(&mut self, params: &Argon2Params) -> Result<Secret<String>, AppError> {
+        let code = self.content.new_recovery_code(params)?;
+        self.save()?;
+        Ok(code)
+    }
+
+    pub fn add_to_vault(&mut self, vault: &Vault) -> Result<(), AppError> {
+        self.content.add_to_vault(vault)?;
+        self.save()
+    }
+
+    pub fn export(&self, path: &Path, passphrase: Secret<String>) -> Result<(), AppError> {
+        let text = self.text().ok_or(AppError::internal("File is not opened"))?;
+        export::write(path, passphrase, text.as_bytes())
+    }
+
+    pub fn set_text(&mut self, new_text: Secret<String>) -> Result<(), AppError> {
+        if let TabContent::Opened { text, .. } = &mut self.content {
+            *text = new_text;
+            self.touch();
+            self.save_changes()
+        } else {
+            Err(AppError::internal("File is not opened"))
+        }
+    }
+
+    /// Keeps saved file as a version if text is changed since the last one
+    pub fn snapshot(&mut self, retention: &Retention) -> Result<(), AppError> {
+        if !self.opened() {
+            return Ok(());
+        }
+        history::add(&mut self.meta, &Tab::path(&self.file)?, retention)
+    }
+
+    pub fn versions(&self) -> Result<Vec<Version>, AppError> {
+        history::versions(&self.meta)
+    }
+
+    pub fn version_text(&self, id: &str) -> Result<Secret<String>, AppError> {
+        self.read_copy(&history::path(&self.meta, id)?)
+    }
+
+    /// Current text is kept as a version first, so restore can be undone
+    pub fn restore_version(&mut self, id: &str, retention: &Retention) -> Result<(), AppError> {
+        // Read before the new version is added, it may prune the restored one
+        let version = self.version_text(id)?;
+        self.save()?;
+        self.snapshot(retention)?;
+        self.replace_text(version)?;
+        self.save_changes()
+    }
+
+    /// Text from recovery journal, it is left unsaved, so it can be checked before save
+    pub fn recover(&mut self, path: &Path) -> Result<(), AppError> {
+        let text = self.read_copy(path)?;
+        self.replace_text(text)
+    }
+
+    fn replace_text(&mut self, new_text: Secret<String>) -> Result<(), AppError> {
+        match &mut self.content {
+            TabContent::Clear { text } => *text = new_text.expose().to_string(),
+            TabContent::Opened { text, .. } => *text = new_text,
+            TabContent::Closed { .. } => return Err(AppError::internal("File is not opened"))
+        }
+        self.touch();
+        Ok(())
+    }
+
+    pub fn lock(&mut self) -> Result<(), AppError> {
+        if let TabContent::Opened { key, title, .. } = &self.content {
+            let (key_file, vault, hidden_name) = (key.has_key_file(), key.in_vault(), title.is_some());
+            self.save()?;
+            self.dirty = false;
+            self.content = TabContent::closed(key_file, vault, hidden_name);
+            if hidden_name {
+                self.name.clear();
+            }
+        }
         Ok(())
     }
 
     pub(super) fn save(&self) -> Result<(), AppError> {
-        let saving_path = Tab::saving_path(&self.name)?;
+        self.write(false)
+    }
+
+    /// Writes content to another file, e.g. recovery journal
+    pub fn save_copy(&self, path: &Path) -> Result<(), AppError> {
+        let mut file = File::create(path)?;
+        self.content.save(&mut file)?;
+        Ok(())
+    }
+
+    /// Reads text of file written by `save_copy` or of an old version of this file
+    pub fn read_copy(&self, path: &Path) -> Result<Secret<String>, AppError> {
+        let mut file = File::open(path)?;
+        self.content.read_version(&mut file)
+    }
+
+    fn write(&self, wipe: bool) -> Result<(), AppError> {
+        let saving_path = Tab::saving_path(&self.file)?;
         let mut saving_file = File::create(&saving_path)?;
         if self.content.save(&mut saving_file)? {
-            let path = Tab::path(&self.name)?;
-            fs::remove_file(&path)?;
+            let path = Tab::path(&self.file)?;
+            // Missing when file is just moved to another name
+            if path.exists() {
+                if wipe {
+                    state::wipe(&path)?;
+                }
+                fs::remove_file(&path)?;
+            }
             fs::rename(&saving_path, &path)?;
         } else {
             fs::remove_file(&saving_path)?;
@@ -63,15 +386,25 @@ impl Tab {
         Ok(())
     }
 
-    pub fn remove(&self) -> Result<PathBuf, AppError> {
+    /// Moves file out of docs as it is, protected file stays encrypted
+    pub fn remove(&self, destination: &Path) -> Result<(), AppError> {
         self.save()?;
+        fs::rename(Tab::path(&self.file)?, destination)?;
+        Ok(())
+    }
 
-        let path = Tab::path(&self.name)?;
-        let del_path = Tab::del_path(&self.name)?;
-
-        fs::rename(&path, &del_path)?;
+    /// Moves file back into docs, metadata is set by caller as on load
+    pub fn restore(id: u64, file: &str, source: &Path) -> Result<Self, AppError> {
+        let path = Tab::path(file)?;
+        if path.exists() {
+            return Err(AppError::FileAlreadyExist);
+        }
 
-        Ok(del_path)
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(source, &path)?;
+        Tab::load(id, file)
     }
 
     pub fn opened(&self) -> bool {
@@ -81,30 +414,57 @@ impl Tab {
         }
     }
 
-    fn path(name: impl AsRef<str>) -> Result<PathBuf, AppError> {
-        Ok(state::docs_path()?
-            .join(name.as_ref())
-            .with_extension("txt"))
+    pub fn clear(&self) -> bool {
+        matches!(self.content, TabContent::Clear { .. })
     }
 
-    fn saving_path(name: impl AsRef<str>) -> Result<PathBuf, AppError> {
-        Ok(state::docs_path()?
-            .join(name.as_ref())
-            .with_extension("saving.txt"))
+    pub fn requires_key_file(&self) -> bool {
+        match &self.content {
+            TabContent::Closed { key_file, .. } => key_file.is_some(),
+            _ => false
+        }
     }
 
-    fn del_path(name: impl AsRef<str>) -> Result<PathBuf, AppError> {
-        let mut index = 0;
-        loop {
-            let path = state::docs_path()?
-                .join(name.as_ref())
-                .with_extension(format!("{}.del.txt", index));
+    pub fn entered_password(&self) -> &str {
+        match &self.content {
+            TabContent::Closed { password, .. } => password.value(),
+            _ => ""
+        }
+    }
 
-            if !path.exists() {
-                return Ok(path);
-            }
+    pub fn in_vault(&self) -> bool {
+        match &self.content {
+            TabContent::Opened { key, .. } => key.in_vault(),
+            TabContent::Closed { vault, .. } => *vault,
+            TabContent::Clear { .. } => false
+        }
+    }
+
+    pub fn unlocked(&self) -> bool {
+        matches!(self.content, TabContent::Opened { .. })
+    }
 
-            index += 1;
+    pub fn join(folder: &str, name: &str) -> String {
+        if folder.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", folder, name)
         }
     }
+
+    fn file_name(file: &str) -> &str {
+        file
+            .rsplit_once('/')
+            .map_or(file, |(_, name)| name)
+    }
+
+    // Extension is appended, so dots in the name are kept
+    fn path(name: impl AsRef<str>) -> Result<PathBuf, AppError> {
+        Ok(state::docs_path()?.join(format!("{}.txt", name.as_ref())))
+    }
+
+    fn saving_path(name: impl AsRef<str>) -> Result<PathBuf, AppError> {
+        Ok(state::docs_path()?.join(format!("{}.saving.txt", name.as_ref())))
+    }
+
 }
\ No newline at end of file
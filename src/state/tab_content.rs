@@ -1,12 +1,22 @@
 use std::fs::File;
-use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::io::{Read, Write};
 use druid::{Data};
+use zeroize::Zeroize;
 use crate::error::AppError;
-use crate::{gen, hash};
+use crate::hash;
 use crate::aes::Aes;
+use crate::format::{Cipher, Format, Header, Slot};
+use crate::gcm::Gcm;
+use crate::hash::Argon2Params;
+use crate::secret::{self, Secret};
+use crate::state::key::{Factors, Key};
+use crate::state::key_file::KeyFile;
+use crate::state::vault::Vault;
+use crate::stream;
 use crate::ui::password_text::PasswordText;
 
-const HEADER : [u8; 3] = [b'E', b'N', b'C'];
+// Text, key of versioned file and title when file name is hidden
+type Decrypted = (Secret<String>, Option<Key>, Option<String>);
 
 #[derive(Clone, Data, Debug, PartialEq)]
 pub enum TabContent {
@@ -14,71 +24,293 @@ pub enum TabContent {
         text: String
     },
     Opened {
-        text: String,
-        password: String
+        text: Secret<String>,
+        key: Key,
+        // Present only when name is hidden and stored encrypted with the text
+        title: Option<String>
     },
     Closed {
-        password: PasswordText
+        password: PasswordText,
+        // Present only when file requires a key file
+        key_file: Option<KeyFile>,
+        vault: bool,
+        hidden_name: bool
     }
 }
 
 impl TabContent {
-    pub fn new(password: Option<String>) -> Result<Self, AppError> {
-        Ok(match password {
-            None => TabContent::Clear {
+    pub fn new(key: Option<Key>, title: Option<String>) -> Result<Self, AppError> {
+        match (key, title) {
+            (None, None) => Ok(TabContent::Clear {
                 text: String::new()
-            },
-            Some(password) => TabContent::Opened {
-                text: String::new(),
-                password
-            }
-        })
+            }),
+            (None, Some(_)) => Err(AppError::internal("Name of clear file can't be hidden")),
+            (Some(key), title) => Ok(TabContent::Opened {
+                text: Secret::empty(),
+                key,
+                title
+            })
+        }
+    }
+
+    pub fn closed(key_file: bool, vault: bool, hidden_name: bool) -> Self {
+        TabContent::Closed {
+            password: PasswordText::new(),
+            key_file: if key_file { Some(KeyFile::new()) } else { None },
+            vault,
+            hidden_name
+        }
     }
 
     pub fn read(file: &mut File) -> Result<Self, AppError> {
-        let mut header = [0u8; 3];
-        if let Ok(_) = file.read_exact(&mut header) {
-            if header == HEADER {
-                return Ok(TabContent::Closed {
-                    password: PasswordText::new()
-                })
-            }
+        match Format::detect(file)? {
+            Format::Versioned => {
+                // Broken header is reported on open, not on load
+                let (key_file, vault, hidden_name) = Header::read(file)
+                    .map(|(header, _)| (header.requires_key_file(), header.in_vault(), header.title))
+                    .unwrap_or((false, false, false));
+                return Ok(TabContent::closed(key_file, vault, hidden_name))
+            },
+            Format::Legacy => return Ok(TabContent::closed(false, false, false)),
+            Format::Clear => {}
         }
 
         let mut text = String::new();
-        file.seek(SeekFrom::Start(0))?;
         file.read_to_string(&mut text)?;
         Ok(TabContent::Clear {
             text
         })
     }
 
-    pub fn open(&self, file: &mut File) -> Result<Self, AppError> {
-        if let TabContent::Closed { password } = &self {
-            let mut header = [0u8; 3];
-            file.read_exact(&mut header)?;
+    pub fn open(&self, file: &mut File, params: &Argon2Params, vault: &Vault) -> Result<Self, AppError> {
+        if let TabContent::Closed { password, key_file, .. } = &self {
+            let key_file = match key_file {
+                Some(key_file) => key_file.hash()?,
+                None => None
+            };
+            let factors = Factors::new(Secret::new(password.value().to_string()), key_file);
+            TabContent::unlock(file, &factors, params, vault)
+        } else {
+            Err(AppError::internal("File is not closed"))
+        }
+    }
+
+    /// Decrypts file with given factors, used where password is not entered into the tab
+    pub fn unlock(file: &mut File, factors: &Factors, params: &Argon2Params, vault: &Vault) -> Result<Self, AppError> {
+        let (text, key, title) = match Format::detect(file)? {
+            Format::Versioned => TabContent::decrypt(factors, vault, file)?,
+            Format::Legacy => (TabContent::decrypt_legacy(factors.password.expose(), file)?, None, None),
+            Format::Clear => return Err(AppError::internal("File is not encrypted"))
+        };
+
+        // Files without data key get one, so they are upgraded on next save
+        let key = match key {
+            Some(key) => key,
+            None => {
+                let mut key = Key::generate()?;
+                key.set_password(factors, params)?;
+                key
+            }
+        };
+
+        Ok(TabContent::Opened {
+            text,
+            key,
+            title
+        })
+    }
+
+    /// Reads text of another file of this tab, e.g. an old version, protected one is decrypted with key of the tab
+    pub fn read_version(&self, file: &mut File) -> Result<Secret<String>, AppError> {
+        match (Format::detect(file)?, self) {
+            (Format::Clear, TabContent::Closed { .. }) => Err(AppError::internal("File is not opened")),
+            (Format::Clear, _) => {
+                let mut text = String::new();
+                file.read_to_string(&mut text)?;
+                Ok(Secret::new(text))
+            },
+            (Format::Versioned, TabContent::Opened { key, .. }) => {
+                let (header, aad) = Header::read(file)?;
+                let (text, _) = TabContent::decrypt_with(key, &header, &aad, file)?;
+                Ok(text)
+            },
+            (Format::Versioned, TabContent::Clear { .. }) => Err(AppError::internal("Version is protected, but note is not")),
+            _ => Err(AppError::internal("Version can't be opened"))
+        }
+    }
+
+    /// Files in older formats are upgraded when they are written, so they are saved after open even if not edited
+    pub fn outdated(file: &mut File) -> Result<bool, AppError> {
+        match Format::detect(file)? {
+            Format::Versioned => {
+                let (header, _) = Header::read(file)?;
+                let direct = header.slots.iter().any(|s| matches!(s, Slot::Direct { .. }));
+                Ok(direct || matches!(header.cipher, Cipher::Aes256Gcm))
+            },
+            Format::Legacy => Ok(true),
+            Format::Clear => Ok(false)
+        }
+    }
+
+    fn decrypt(factors: &Factors, vault: &Vault, file: &mut File) -> Result<Decrypted, AppError> {
+        let (header, aad) = Header::read(file)?;
+        let key = Key::open(&header.slots, factors, vault)?;
+        let (text, title) = TabContent::decrypt_with(&key, &header, &aad, file)?;
+
+        if key.is_outdated() {
+            Ok((text, None, title))
+        } else {
+            Ok((text, Some(key), title))
+        }
+    }
+
+    fn decrypt_with(key: &Key, header: &Header, aad: &[u8], file: &mut File) -> Result<(Secret<String>, Option<String>), AppError> {
+        let capacity = file.metadata()?.len() as usize;
+        let text_bytes = match header.cipher {
+            Cipher::Aes256Gcm => {
+                let mut data = Vec::with_capacity(capacity);
+                file.read_to_end(&mut data)?;
+                Gcm::new(key.bytes()).decrypt(&header.nonce, aad, &data)?
+            },
+            Cipher::Aes256GcmStream => stream::decrypt(key.bytes(), &header.nonce, aad, file, capacity)?
+        };
+
+        if header.title {
+            TabContent::split_title(text_bytes)
+        } else {
+            Ok((Secret::from_utf8(text_bytes)?, None))
+        }
+    }
+
+    fn split_title(mut bytes: Vec<u8>) -> Result<(Secret<String>, Option<String>), AppError> {
+        let title = match Self::read_title(&bytes) {
+            Ok(title) => title,
+            Err(e) => {
+                bytes.zeroize();
+                return Err(e);
+            }
+        };
+
+        // Drained in place, rest of the buffer is wiped with the text
+        bytes.drain(..2 + title.len());
+        Ok((Secret::from_utf8(bytes)?, Some(title)))
+    }
+
+    fn read_title(bytes: &[u8]) -> Result<String, AppError> {
+        if bytes.len() < 2 {
+            return Err(AppError::Damaged);
+        }
+        let len = u16::from_le_bytes([bytes[0], bytes[1]]) as usize;
+        let title = bytes.get(2..2 + len).ok_or(AppError::Damaged)?;
+        String::from_utf8(title.to_vec()).map_err(|_| AppError::Damaged)
+    }
+
+    fn payload(title: &str, text: &Secret<String>) -> Result<Secret<Vec<u8>>, AppError> {
+        let len = u16::try_from(title.len()).map_err(|_| AppError::internal("Name is too long"))?;
+        let text = text.expose().as_bytes();
+
+        let mut payload = Secret::new(Vec::with_capacity(2 + title.len() + text.len()));
+        payload.edit(|p| {
+            p.extend_from_slice(&len.to_le_bytes());
+            p.extend_from_slice(title.as_bytes());
+            p.extend_from_slice(text);
+        });
+        Ok(payload)
+    }
+
+    fn decrypt_legacy(password: &str, file: &mut File) -> Result<Secret<String>, AppError> {
+        let mut header = [0u8; 3];
+        file.read_exact(&mut header)?;
+
+        let mut hash = [0u8; hash::STORAGE_HASH_SIZE];
+        file.read_exact(&mut hash)?;
 
-            let mut hash = [0u8; hash::STORAGE_HASH_SIZE];
-            file.read_exact(&mut hash)?;
+        hash::storage_check(password, &hash)?;
 
-            hash::storage_check(password.value(), &hash)?;
+        let mut iv = [0u8; 16];
+        file.read_exact(&mut iv)?;
 
-            let mut iv = [0u8; 16];
-            file.read_exact(&mut iv)?;
+        let key = hash::cypher(password);
+        let mut aes = Aes::decryptor(&iv, key.expose());
+        let mut text_bytes = secret::text_buffer(file)?;
+        aes.write(file, &mut text_bytes)?;
 
-            let key = hash::cypher(password.value());
-            let mut aes = Aes::decryptor(&iv, &key);
-            let mut text_bytes = Cursor::new(Vec::<u8>::new());
-            aes.write(file, &mut text_bytes)?;
+        Secret::from_utf8(text_bytes)
+    }
 
+    /// Text is moved out of the clear content, so no unwiped copy is left behind
+    pub fn protect(&mut self, key: Key) -> Result<Self, AppError> {
+        if let TabContent::Clear { text } = self {
             Ok(TabContent::Opened {
-                text: String::from_utf8(text_bytes.into_inner())?,
-                password: password
-                    .value()
-                    .to_string()
+                text: Secret::new(std::mem::take(text)),
+                key,
+                title: None
             })
         } else {
-            Err(AppError::internal("File is not closed"))
+            Err(AppError::internal("File is already protected"))
+        }
+    }
+
+    pub fn unprotect(&self, current: &str, vault: &Vault) -> Result<Self, AppError> {
+        if let TabContent::Opened { text, key, .. } = self {
+            key.verify(current, vault)?;
+            Ok(TabContent::Clear {
+                text: text
+                    .expose()
+                    .clone()
+            })
+        } else {
+            Err(AppError::internal("File is not opened"))
+        }
+    }
+
+    pub fn change_password(&mut self, current: &str, password: Secret<String>, params: &Argon2Params, vault: &Vault) -> Result<(), AppError> {
+        if let TabContent::Opened { key, .. } = self {
+            key.verify(current, vault)?;
+            key.change_password(password, params)?;
+            Ok(())
+        } else {
+            Err(AppError::internal("File is not opened"))
+        }
+    }
+
+    pub fn new_recovery_code(&mut self, params: &Argon2Params) -> Result<Secret<String>, AppError> {
+        if let TabContent::Opened { key, .. } = self {
+            key.new_recovery_code(params)
+        } else {
+            Err(AppError::internal("File is not opened"))
+        }
+    }
+
+    pub fn add_to_vault(&mut self, vault: &Vault) -> Result<(), AppError> {
+        if let TabContent::Opened { key, .. } = self {
+            key.add_to_vault(vault)
+        } else {
+            Err(AppError::internal("File is not opened"))
+        }
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        match self {
+            TabContent::Opened { title, .. } => title.as_deref(),
+            _ => None
+        }
+    }
+
+    pub fn set_title(&mut self, new_title: Option<String>) -> Result<(), AppError> {
+        if let TabContent::Opened { title, .. } = self {
+            *title = new_title;
+            Ok(())
+        } else {
+            Err(AppError::internal("File is not opened"))
+        }
+    }
+
+    pub fn hidden_name(&self) -> bool {
+        match self {
+            TabContent::Opened { title, .. } => title.is_some(),
+            TabContent::Closed { hidden_name, .. } => *hidden_name,
+            TabContent::Clear { .. } => false
         }
     }
 
@@ -89,19 +321,28 @@ impl TabContent {
                 file.flush()?;
                 true
             },
-            TabContent::Opened { text, password } => {
-                file.write(&HEADER)?;
-
-                let hash = hash::storage_build(&password)?;
-                file.write(&hash)?;
+            TabContent::Opened { text, key, title } => {
+                // Always written in current format, so legacy files are upgraded on save
+                let header = Header::new(key.slots().to_vec(), title.is_some())?;
+                let aad = header.to_bytes()?;
+                file.write_all(&aad)?;
 
-                let iv = gen::iv()?;
-                file.write(&iv)?;
+                let payload = match title {
+                    Some(title) => Some(TabContent::payload(title, text)?),
+                    None => None
+                };
+                let data = match &payload {
+                    Some(payload) => payload.expose().as_slice(),
+                    None => text.expose().as_bytes()
+                };
 
-                let key = hash::cypher(&password);
-                let mut aes = Aes::encryptor(&iv, &key);
-                let mut text_bytes = Cursor::new(text.as_bytes());
-                aes.write(&mut text_bytes, file)?;
+                match header.cipher {
+                    Cipher::Aes256Gcm => {
+                        let data = Gcm::new(key.bytes()).encrypt(&header.nonce, &aad, data)?;
+                        file.write_all(&data)?;
+                    },
+                    Cipher::Aes256GcmStream => stream::encrypt(key.bytes(), &header.nonce, &aad, data, file)?
+                }
 
                 file.flush()?;
                 true
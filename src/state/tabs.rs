@@ -1,90 +1,251 @@
 use std::{fs, collections};
 use std::fs::File;
 use std::io::Write;
+use std::path::Path;
 use druid::Data;
 use druid::im::{HashMap, Vector};
-use crate::state;
+use crate::{paths, state};
 use crate::error::AppError;
+use crate::hash::Argon2Params;
+use crate::state::history::Retention;
+use crate::state::journal;
+use crate::state::index::{Index, LabelColor, Meta};
+use crate::state::key::Key;
 use crate::state::tab::Tab;
+use crate::state::vault::Vault;
+
+// Revision, tabs by id and folders found in docs
+type Loaded = (u64, collections::HashMap::<u64, Tab>, Vec<String>);
+
+// Device names of Windows, they can't be used as file names with any extension
+const RESERVED_NAMES : [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9"
+];
 
 #[derive(Clone, Data)]
 pub struct Tabs {
     tabs: HashMap<u64, Tab>,
-    to_remove: Vector<String>,
+    // Subfolders of docs with `/` separators, sorted
+    folders: Vector<String>,
+    // Folder which tabs are shown in tab strip, pinned tabs are shown in any
+    folder: String,
+    // Autosave is not retried until next edit, so its error is reported once
+    autosave_failed: bool,
     rev: u64
 }
 
 impl Tabs {
     pub fn load() -> Result<Self, AppError> {
-        let (rev, tabs) = Self::load_tabs()?;
+        Self::create_docs()?;
+        Self::open()
+    }
+
+    /// Loads notes as they are on disk, docs folder is not created when missing
+    pub fn open() -> Result<Self, AppError> {
+        let (rev, tabs, folders) = Self::load_tabs()?;
         Ok(Tabs {
             tabs: HashMap::from(tabs),
-            to_remove: Vector::new(),
+            folders: Vector::from(folders),
+            folder: String::new(),
+            autosave_failed: false,
             rev
         })
     }
 
     pub fn reload(&mut self) -> Result<(), AppError> {
-        let (_, tabs) = Self::load_tabs()?;
+        Self::create_docs()?;
+        let (_, tabs, folders) = Self::load_tabs()?;
 
-        for (_, loaded_tab) in tabs {
+        for (_, mut loaded_tab) in tabs {
             let pair = self.tabs
                 .iter_mut()
-                .find(|p| p.1.name == loaded_tab.name);
+                .find(|p| p.1.file == loaded_tab.file);
 
             if let Some((_, tab)) = pair {
                 tab.update(&loaded_tab);
             } else {
                 self.rev += 1;
+                loaded_tab.id = self.rev;
                 self.tabs.insert(self.rev, loaded_tab);
             }
         }
 
+        self.folders = Vector::from(folders);
+        self.rev += 1;
         Ok(())
     }
 
-    fn load_tabs() -> Result<(u64, collections::HashMap::<u64, Tab>), AppError> {
+    fn create_docs() -> Result<(), AppError> {
+        let docs_path = state::docs_path()?;
+        if !docs_path.exists() {
+            fs::create_dir(&docs_path)?;
+            let mut file = File::create(docs_path.join("text.txt"))?;
+            write!(file, "New text")?;
+        }
+        Ok(())
+    }
+
+    fn load_tabs() -> Result<Loaded, AppError> {
         let mut tabs = collections::HashMap::<u64, Tab>::new();
         let mut rev = 0;
         let docs_path = state::docs_path()?;
-        let docs = match state::docs(&docs_path) {
-            Ok(p) => p,
-            Err(_) => {
-                fs::create_dir(&docs_path)?;
-                let mut file = File::create(docs_path.join("text.txt"))?;
-                write!(file, "New text")?;
-                state::docs(&docs_path)?
-            }
+        let docs = if docs_path.exists() {
+            state::docs(&docs_path)?
+        } else {
+            Vec::new()
         };
 
+        let mut folders = Vec::new();
         for path in docs {
-            if path.is_file() {
-                let name = path
-                    .file_stem().ok_or(AppError::internal("Not a file"))?
-                    .to_str().ok_or(AppError::internal("Invalid file name"))?;
+            if path.is_dir() {
+                folders.push(paths::relative(&docs_path, &path)?);
+            }
+            // Vault and other service files are not tabs
+            if path.is_file() && path.extension() == Some("txt".as_ref()) {
+                let file = paths::relative(&docs_path, &path.with_extension(""))?;
 
                 rev += 1;
-                let tab = Tab::load(rev, &name)?;
+                let tab = Tab::load(rev, &file)?;
                 tabs.insert(tab.id, tab);
             }
         }
+        folders.sort();
+
+        // Notes missing in index go after known ones, in file name order
+        let mut index = Index::load()?;
+        let mut unknown: Vec<&mut Tab> = tabs
+            .values_mut()
+            .filter(|t| index.get(&t.file).is_none())
+            .collect();
+        unknown.sort_by(|a, b| a.file.cmp(&b.file));
+        for tab in unknown {
+            index.insert(tab.file.clone(), Meta::new(index.next_position()));
+        }
+
+        for tab in tabs.values_mut() {
+            tab.meta = index.get(&tab.file).cloned().ok_or(AppError::internal("Index error"))?;
+        }
 
-        Ok((rev, tabs))
+        Ok((rev, tabs, folders))
     }
 
+    /// Tabs of tab strip: pinned ones go first, then tabs of current folder ordered by position from index
     pub fn keys(&self) -> Vec<u64> {
+        self.sorted_keys(|tab| tab.meta.pinned || tab.folder() == self.folder)
+    }
+
+    /// Tabs of folder without nested folders
+    pub fn folder_keys(&self, folder: &str) -> Vec<u64> {
+        self.sorted_keys(|tab| tab.folder() == folder)
+    }
+
+    pub fn all_keys(&self) -> Vec<u64> {
+        self.sorted_keys(|_| true)
+    }
+
+    fn sorted_keys(&self, filter: impl Fn(&Tab) -> bool) -> Vec<u64> {
         let mut keys = Vec::<u64>::new();
-        for (key, _) in &self.tabs {
-            keys.push(*key);
+        for (key, tab) in &self.tabs {
+            if filter(tab) {
+                keys.push(*key);
+            }
         }
-        keys.sort();
+        keys.sort_by_key(|k| {
+            let meta = &self.get(*k).meta;
+            (!meta.pinned, meta.position, *k)
+        });
         keys
     }
 
+    pub fn folder(&self) -> &str {
+        &self.folder
+    }
+
+    pub fn set_folder(&mut self, folder: &str) {
+        if self.folder != folder {
+            self.folder = folder.to_string();
+            self.rev += 1;
+        }
+    }
+
+    pub fn folders(&self) -> &Vector<String> {
+        &self.folders
+    }
+
+    /// Folders right inside of given one, empty parent is docs itself
+    pub fn subfolders(&self, parent: &str) -> Vec<String> {
+        self.folders
+            .iter()
+            .filter(|f| f.rsplit_once('/').map_or("", |(p, _)| p) == parent)
+            .cloned()
+            .collect()
+    }
+
+    pub fn create_folder(&mut self, parent: &str, name: &str) -> Result<(), AppError> {
+        let name = Tabs::check_name(name)?;
+        let folder = Tab::join(parent, name);
+        let path = state::docs_path()?.join(&folder);
+        if path.exists() {
+            return Err(AppError::FileAlreadyExist);
+        }
+
+        fs::create_dir(path)?;
+        self.folders.push_back(folder);
+        self.folders.sort();
+        self.rev += 1;
+        Ok(())
+    }
+
+    pub fn can_remove_folder(&self, folder: &str) -> bool {
+        !folder.is_empty()
+            && self.subfolders(folder).is_empty()
+            && self.folder_keys(folder).is_empty()
+    }
+
+    pub fn remove_folder(&mut self, folder: &str) -> Result<(), AppError> {
+        if !self.can_remove_folder(folder) {
+            return Err(AppError::FolderNotEmpty);
+        }
+
+        fs::remove_dir(state::docs_path()?.join(folder))?;
+        self.folders.retain(|f| f != folder);
+        if self.folder == folder {
+            self.folder = String::new();
+        }
+        self.rev += 1;
+        Ok(())
+    }
+
+    pub fn rename(&mut self, key: u64, name: &str) -> Result<(), AppError> {
+        let name = Tabs::check_name(name)?;
+        let tab = self.get(key);
+        let file = Tab::join(tab.folder(), name);
+        if !tab.hidden_name() && self.file_taken(&file, Some(key)) {
+            return Err(AppError::FileAlreadyExist);
+        }
+
+        self.get_mut(key).rename(name.to_string())?;
+        self.rev += 1;
+        self.save_index()
+    }
+
+    pub fn move_tab_to(&mut self, key: u64, folder: &str) -> Result<(), AppError> {
+        self.get_mut(key).move_to_folder(folder)?;
+        self.rev += 1;
+        self.save_index()
+    }
+
     pub fn rev(&self) -> u64 {
         self.rev
     }
 
+    /// Windows opened for a tab may outlive it
+    pub fn contains(&self, key: u64) -> bool {
+        self.tabs.contains_key(&key)
+    }
+
     pub fn get(&self, key: u64) -> &Tab {
         self.tabs.get(&key).expect("Index error")
     }
@@ -93,44 +254,273 @@ impl Tabs {
         self.tabs.get_mut(&key).expect("Index error")
     }
 
-    pub fn add(&mut self, name: String, password: Option<String>) -> Result<(), AppError> {
-        for (_, tab) in &self.tabs {
-            if tab.name == name {
+    // Label widget may outlive its tab until tabs are rebuilt
+    pub fn label(&self, key: u64) -> String {
+        match self.tabs.get(&key) {
+            Some(tab) => {
+                let pin = if tab.meta.pinned { "📌 " } else { "" };
+                let unsaved = if tab.dirty { " ●" } else { "" };
+                format!("{}{}{}", pin, tab.label(), unsaved)
+            },
+            None => String::new()
+        }
+    }
+
+    pub fn color(&self, key: u64) -> Option<LabelColor> {
+        self.tabs
+            .get(&key)
+            .and_then(|t| t.meta.color)
+    }
+
+    pub fn add(&mut self, name: String, key: Option<Key>, hide_name: bool) -> Result<u64, AppError> {
+        if !hide_name {
+            let file = Tab::join(&self.folder, Tabs::check_name(&name)?);
+            if self.file_taken(&file, None) {
                 return Err(AppError::FileAlreadyExist)
             }
         }
 
         self.rev += 1;
-        let tab = Tab::new(self.rev, name, password)?;
-        self.tabs.insert(tab.id, tab);
+        let mut tab = Tab::new(self.rev, &self.folder, name, key, hide_name)?;
+        tab.meta.position = self.next_position();
+        let id = tab.id;
+        self.tabs.insert(id, tab);
+        self.save_index()?;
 
-        Ok(())
+        Ok(id)
     }
 
+    /// Forgets tab, its file is expected to be moved to trash already
     pub fn remove(&mut self, key: u64) -> Result<(), AppError> {
-        let tab = self.tabs.remove(&key).ok_or(AppError::internal("Invalid key"))?;
+        self.tabs.remove(&key).ok_or(AppError::internal("Invalid key"))?;
+        self.rev += 1;
+        self.write_journal()?;
+        self.save_index()
+    }
 
-        let path = tab.remove()?;
-        let file_name = path
-            .to_str().ok_or(AppError::internal("Invalid file name"))?
-            .to_string();
+    pub fn restore(&mut self, file: &str, meta: Meta, source: &Path) -> Result<u64, AppError> {
+        self.rev += 1;
+        let mut tab = Tab::restore(self.rev, file, source)?;
+        tab.meta = meta;
+        let id = tab.id;
 
-        self.to_remove.push_back(file_name);
+        // Folder of note may have been removed after it went to trash
+        let mut folder = String::new();
+        for name in tab.folder().split('/').filter(|n| !n.is_empty()) {
+            folder = Tab::join(&folder, name);
+            if !self.folders.contains(&folder) {
+                self.folders.push_back(folder.clone());
+            }
+        }
+        self.folders.sort();
+
+        self.tabs.insert(id, tab);
+        self.save_index()?;
+        Ok(id)
+    }
+
+    /// Swaps tab with its neighbour, pinned and other tabs are moved only among themselves
+    pub fn move_tab(&mut self, key: u64, right: bool) -> Result<(), AppError> {
+        if let Some(neighbour) = self.neighbour(key, right) {
+            let position = self.get(key).meta.position;
+            self.get_mut(key).meta.position = self.get(neighbour).meta.position;
+            self.get_mut(neighbour).meta.position = position;
+            self.rev += 1;
+            self.save_index()?;
+        }
+        Ok(())
+    }
+
+    pub fn can_move_tab(&self, key: u64, right: bool) -> bool {
+        self.neighbour(key, right).is_some()
+    }
+
+    pub fn set_pinned(&mut self, key: u64, pinned: bool) -> Result<(), AppError> {
+        self.get_mut(key).meta.pinned = pinned;
         self.rev += 1;
+        self.save_index()
+    }
+
+    pub fn set_color(&mut self, key: u64, color: Option<LabelColor>) -> Result<(), AppError> {
+        self.get_mut(key).meta.color = color;
+        self.save_index()
+    }
+
+    pub fn restore_version(&mut self, key: u64, id: &str, retention: &Retention) -> Result<(), AppError> {
+        self.get_mut(key).restore_version(id, retention)?;
+        self.save_index()
+    }
+
+    pub fn lock_all(&mut self) -> Result<(), AppError> {
+        for (_, tab) in self.tabs.iter_mut() {
+            tab.lock()?;
+        }
         Ok(())
     }
 
-    pub(super) fn save(&mut self) -> Result<(), AppError> {
-        for (_, tab) in &self.tabs {
-            tab.save()?;
+    pub fn open_vault(&mut self, params: &Argon2Params, vault: &Vault) -> Result<(), AppError> {
+        for (_, tab) in self.tabs.iter_mut() {
+            if !tab.opened() && tab.in_vault() {
+                tab.open(params, vault)?;
+            }
         }
+        Ok(())
+    }
 
-        for file_name in &self.to_remove {
-            fs::remove_file(&file_name)?;
+    /// Versions are kept only on explicit save, autosave would push them out too soon
+    pub(super) fn save(&mut self, retention: &Retention) -> Result<(), AppError> {
+        for (_, tab) in self.tabs.iter_mut() {
+            tab.save_changes()?;
+            tab.snapshot(retention)?;
         }
+        self.write_journal()?;
+        self.autosave_failed = false;
+        self.save_index()
+    }
 
-        self.to_remove.clear();
+    // Names of notes and folders are used as file names, so they must be valid on every platform
+    pub fn check_name(name: &str) -> Result<&str, AppError> {
+        let name = name.trim();
+        let device = name
+            .split('.')
+            .next()
+            .is_some_and(|stem| RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(stem.trim())));
+        let invalid = name.is_empty()
+            || name.contains(&['/', '\\', '<', '>', ':', '"', '|', '?', '*'][..])
+            || name.chars().any(char::is_control)
+            // Windows drops trailing dots, so "." and ".." are rejected by it too
+            || name.ends_with('.')
+            // Note is written to `<name>.saving.txt` first
+            || name.to_ascii_lowercase().ends_with(".saving")
+            || device;
+        if invalid {
+            return Err(AppError::InvalidName);
+        }
+        Ok(name)
+    }
 
-        Ok(())
+    // Names that differ only in case are the same file on case insensitive file systems
+    fn file_taken(&self, file: &str, except: Option<u64>) -> bool {
+        self.tabs
+            .values()
+            .any(|t| Some(t.id) != except && !t.hidden_name() && t.file.eq_ignore_ascii_case(file))
+    }
+
+    pub(super) fn autosave(&mut self) -> Result<(), AppError> {
+        if !self.is_dirty() {
+            return Ok(());
+        }
+
+        let result = self.save_dirty();
+        self.autosave_failed = result.is_err();
+        result
+    }
+
+    fn save_dirty(&mut self) -> Result<(), AppError> {
+        for (_, tab) in self.tabs.iter_mut() {
+            tab.save_changes()?;
+        }
+        self.write_journal()?;
+        self.save_index()
+    }
+
+    pub fn autosave_pending(&self) -> bool {
+        self.is_dirty() && !self.autosave_failed
+    }
+
+    /// Text of tab is edited by user
+    pub fn touch(&mut self, key: u64) {
+        self.get_mut(key).touch();
+        self.autosave_failed = false;
+    }
+
+    /// Journal is written again when text is changed and when tab is saved, so saved text is dropped from it
+    pub fn journal_pending(&self) -> bool {
+        self.tabs
+            .values()
+            .any(|t| t.dirty != t.journaled)
+    }
+
+    pub fn write_journal(&mut self) -> Result<(), AppError> {
+        let result = journal::write(self.tabs.values());
+        // Failed write is not retried until next edit, so its error is reported once
+        for (_, tab) in self.tabs.iter_mut() {
+            tab.journaled = tab.dirty;
+        }
+        result
+    }
+
+    /// Journal is written by worker thread, its result is passed to `done`
+    pub fn write_journal_in_background(&mut self, done: impl FnOnce(Result<(), AppError>) + Send + 'static) {
+        journal::write_in_background(self.tabs.values(), done);
+        for (_, tab) in self.tabs.iter_mut() {
+            tab.journaled = tab.dirty;
+        }
+    }
+
+    pub fn find(&self, file: &str) -> Option<u64> {
+        self.tabs
+            .values()
+            .find(|t| t.file == file)
+            .map(|t| t.id)
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.tabs
+            .values()
+            .any(|t| t.dirty)
+    }
+
+    fn neighbour(&self, key: u64, right: bool) -> Option<u64> {
+        let keys = self.keys();
+        let index = keys.iter().position(|k| *k == key)?;
+        let neighbour = if right {
+            *keys.get(index + 1)?
+        } else {
+            *keys.get(index.checked_sub(1)?)?
+        };
+
+        if self.get(neighbour).meta.pinned == self.get(key).meta.pinned {
+            Some(neighbour)
+        } else {
+            None
+        }
+    }
+
+    fn next_position(&self) -> u64 {
+        self.tabs
+            .values()
+            .map(|t| t.meta.position + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Index is written from tabs, so renamed and removed files are never left in it
+    fn save_index(&self) -> Result<(), AppError> {
+        let mut index = Index::default();
+        for (_, tab) in &self.tabs {
+            index.insert(tab.file.clone(), tab.meta.clone());
+        }
+        index.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_valid_as_file_names() {
+        for name in ["v1.2", "notes", " Plan for 2024 ", ".hidden", "console"] {
+            assert!(Tabs::check_name(name).is_ok(), "{}", name);
+        }
+        assert_eq!(Tabs::check_name(" Plan ").unwrap(), "Plan");
+    }
+
+    #[test]
+    fn names_invalid_as_file_names() {
+        for name in ["", " ", ".", "..", "a/b", "a\\b", "a:b", "why?", "end.", "draft.saving", "Draft.SAVING", "con", "Nul.txt", "lpt1", "a\tb"] {
+            assert!(matches!(Tabs::check_name(name), Err(AppError::InvalidName)), "{}", name);
+        }
     }
 }
\ No newline at end of file
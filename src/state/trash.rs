@@ -0,0 +1,151 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::fs::File;
+use std::path::PathBuf;
+use chrono::{DateTime, Duration, Utc};
+use druid::Data;
+use druid::im::Vector;
+use serde::{Serialize, Deserialize};
+use crate::error::AppError;
+use crate::{gen, paths, state};
+use crate::state::history;
+use crate::state::index::Meta;
+use crate::state::tab::Tab;
+use crate::state::tabs::Tabs;
+
+const INDEX_FILE_NAME : &str = "index.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Data)]
+pub struct TrashItem {
+    // File name in trash
+    pub id: String,
+    // Path of note in docs, it is restored there
+    pub file: String,
+    pub label: String,
+    #[data(same_fn = "PartialEq::eq")]
+    pub deleted: DateTime<Utc>,
+    pub meta: Meta
+}
+
+/// Removed notes, they are kept outside of docs, so they are not shown or backed up
+#[derive(Clone, Data, Default)]
+pub struct Trash {
+    items: Vector<TrashItem>
+}
+
+impl Trash {
+    pub fn load() -> Result<Self, AppError> {
+        let path = Trash::index_path()?;
+        if !path.exists() {
+            return Ok(Trash::default());
+        }
+
+        let file = File::open(path)?;
+        let items: Vec<TrashItem> = serde_json::from_reader(file)?;
+        Ok(Trash {
+            items: Vector::from(items)
+        })
+    }
+
+    /// Newest first
+    pub fn items(&self) -> Vec<TrashItem> {
+        let mut items: Vec<TrashItem> = self.items
+            .iter()
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| Reverse(item.deleted));
+        items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn put(&mut self, tab: &Tab) -> Result<(), AppError> {
+        fs::create_dir_all(paths::trash_directory()?)?;
+        let id = gen::hex_id()?;
+        tab.remove(&Trash::path(&id)?)?;
+
+        self.items.push_back(TrashItem {
+            id,
+            file: tab.file.clone(),
+            label: tab.public_label().to_string(),
+            deleted: Utc::now(),
+            meta: tab.meta.clone()
+        });
+        self.save()
+    }
+
+    /// Moves note back to its folder, note with the same name must be renamed or removed first
+    pub fn restore(&mut self, id: &str, tabs: &mut Tabs) -> Result<u64, AppError> {
+        let index = self.position(id)?;
+        let item = self.items[index].clone();
+        let key = tabs.restore(&item.file, item.meta, &Trash::path(id)?)?;
+
+        self.items.remove(index);
+        self.save()?;
+        Ok(key)
+    }
+
+    pub fn purge(&mut self, id: &str) -> Result<(), AppError> {
+        let index = self.position(id)?;
+        let path = Trash::path(id)?;
+        if path.exists() {
+            state::wipe(&path)?;
+            fs::remove_file(&path)?;
+        }
+        history::remove_all(&self.items[index].meta)?;
+
+        self.items.remove(index);
+        self.save()
+    }
+
+    pub fn purge_all(&mut self) -> Result<(), AppError> {
+        for item in self.items() {
+            self.purge(&item.id)?;
+        }
+        Ok(())
+    }
+
+    /// Purges notes removed more than given days ago, zero keeps them forever
+    pub fn purge_expired(&mut self, days: u64) -> Result<(), AppError> {
+        if days == 0 {
+            return Ok(());
+        }
+
+        let expired = Utc::now() - Duration::days(days as i64);
+        for item in self.items() {
+            if item.deleted < expired {
+                self.purge(&item.id)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<(), AppError> {
+        fs::create_dir_all(paths::trash_directory()?)?;
+        let items: Vec<&TrashItem> = self.items
+            .iter()
+            .collect();
+        let file = File::create(Trash::index_path()?)?;
+        serde_json::to_writer_pretty(file, &items)?;
+        Ok(())
+    }
+
+    fn position(&self, id: &str) -> Result<usize, AppError> {
+        self.items
+            .iter()
+            .position(|i| i.id == id)
+            .ok_or(AppError::internal("Invalid trash item"))
+    }
+
+    fn path(id: &str) -> Result<PathBuf, AppError> {
+        Ok(paths::trash_directory()?
+            .join(id)
+            .with_extension("txt"))
+    }
+
+    fn index_path() -> Result<PathBuf, AppError> {
+        Ok(paths::trash_directory()?.join(INDEX_FILE_NAME))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,146 @@
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use druid::Data;
+use crate::error::AppError;
+use crate::format::{Kdf, Slot, VAULT_ID_SIZE, VaultHeader, WrappedKey};
+use crate::gen;
+use crate::hash::Argon2Params;
+use crate::secret::Secret;
+use crate::state;
+
+const FILE_NAME : &str = "vault.key";
+
+/// Optional master key shared by protected tabs. It is wrapped with master password,
+/// so password can be changed without touching tabs.
+#[derive(Clone, Data)]
+pub struct Vault {
+    #[data(same_fn = "PartialEq::eq")]
+    header: Option<VaultHeader>,
+    master: Option<Secret<[u8; 32]>>
+}
+
+impl Vault {
+    pub fn load() -> Result<Self, AppError> {
+        let path = Vault::path()?;
+        let header = if path.exists() {
+            let mut file = File::open(path)?;
+            Some(VaultHeader::read(&mut file)?)
+        } else {
+            None
+        };
+
+        Ok(Vault {
+            header,
+            master: None
+        })
+    }
+
+    pub fn exists(&self) -> bool {
+        self.header.is_some()
+    }
+
+    pub fn unlocked(&self) -> bool {
+        self.master.is_some()
+    }
+
+    pub fn create(&mut self, password: &str, params: &Argon2Params) -> Result<(), AppError> {
+        if self.exists() {
+            return Err(AppError::internal("Vault already exists"));
+        }
+
+        let mut id = [0u8; VAULT_ID_SIZE];
+        id.copy_from_slice(&gen::bytes(VAULT_ID_SIZE)?);
+
+        let mut master = Secret::zeroed();
+        master.edit(|m| getrandom::getrandom(m))?;
+
+        let kdf = Kdf::argon2id(*params)?;
+        let kek = kdf.derive(password, None)?;
+        let header = VaultHeader {
+            id,
+            kdf,
+            wrapped: WrappedKey::wrap(kek.expose(), &master)?
+        };
+
+        Vault::write(&header)?;
+        self.header = Some(header);
+        self.master = Some(master);
+        Ok(())
+    }
+
+    pub fn unlock(&mut self, password: &str) -> Result<(), AppError> {
+        let master = self.unwrap(password)?;
+        self.master = Some(master);
+        Ok(())
+    }
+
+    pub fn lock(&mut self) {
+        self.master = None;
+    }
+
+    /// Only master key is rewrapped, tabs keep their slots
+    pub fn change_password(&mut self, current: &str, password: &str, params: &Argon2Params) -> Result<(), AppError> {
+        let master = self.unwrap(current)?;
+
+        let kdf = Kdf::argon2id(*params)?;
+        let kek = kdf.derive(password, None)?;
+        let header = VaultHeader {
+            id: self.header()?.id,
+            kdf,
+            wrapped: WrappedKey::wrap(kek.expose(), &master)?
+        };
+
+        Vault::write(&header)?;
+        self.header = Some(header);
+        self.master = Some(master);
+        Ok(())
+    }
+
+    pub fn verify(&self, password: &str) -> Result<(), AppError> {
+        self.unwrap(password).map(|_| ())
+    }
+
+    /// Slot that gives data key to anyone who has this vault unlocked
+    pub fn slot(&self, data: &Secret<[u8; 32]>) -> Result<Slot, AppError> {
+        let master = self.master.as_ref().ok_or(AppError::VaultLocked)?;
+        Ok(Slot::Vault {
+            id: self.header()?.id,
+            wrapped: WrappedKey::wrap(master.expose(), data)?
+        })
+    }
+
+    pub fn master(&self, id: &[u8; VAULT_ID_SIZE]) -> Option<&Secret<[u8; 32]>> {
+        match (&self.header, &self.master) {
+            (Some(header), Some(master)) if &header.id == id => Some(master),
+            _ => None
+        }
+    }
+
+    fn unwrap(&self, password: &str) -> Result<Secret<[u8; 32]>, AppError> {
+        let header = self.header()?;
+        let kek = header.kdf.derive(password, None)?;
+        header.wrapped.unwrap(kek.expose())
+    }
+
+    fn header(&self) -> Result<&VaultHeader, AppError> {
+        self.header.as_ref().ok_or(AppError::internal("Vault is not created"))
+    }
+
+    fn write(header: &VaultHeader) -> Result<(), AppError> {
+        let path = Vault::path()?;
+        let saving_path = path.with_extension("saving.key");
+
+        let mut file = File::create(&saving_path)?;
+        file.write_all(&header.to_bytes()?)?;
+        file.sync_all()?;
+
+        fs::rename(&saving_path, &path)?;
+        Ok(())
+    }
+
+    fn path() -> Result<PathBuf, AppError> {
+        Ok(state::docs_path()?.join(FILE_NAME))
+    }
+}
\ No newline at end of file
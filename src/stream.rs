@@ -0,0 +1,171 @@
+use std::io::{Read, Write};
+use aes_gcm::{Aes256Gcm, Key};
+use aes_gcm::aead::KeyInit;
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::stream::{DecryptorBE32, EncryptorBE32};
+use zeroize::Zeroize;
+use crate::error::AppError;
+
+/// Text is split into chunks authenticated on their own (STREAM construction),
+/// last chunk is marked, so truncated file is detected too.
+pub const CHUNK_SIZE : usize = 64 * 1024;
+const TAG_SIZE : usize = 16;
+// Rest of the nonce is chunk counter and last chunk flag
+const NONCE_PREFIX_SIZE : usize = 7;
+
+pub fn encrypt<W: Write>(key: &[u8], nonce: &[u8], aad: &[u8], data: &[u8], output: &mut W) -> Result<(), AppError> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let mut encryptor = EncryptorBE32::from_aead(cipher, GenericArray::from_slice(&nonce[..NONCE_PREFIX_SIZE]));
+    let mut buffer = Vec::with_capacity(CHUNK_SIZE + TAG_SIZE);
+
+    let mut chunks = data.chunks(CHUNK_SIZE);
+    // Empty text is still written as one last chunk
+    let mut chunk = chunks.next().unwrap_or(&[]);
+    loop {
+        buffer.clear();
+        buffer.extend_from_slice(chunk);
+
+        match chunks.next() {
+            Some(next) => {
+                encryptor
+                    .encrypt_next_in_place(aad, &mut buffer)
+                    .map_err(|_| AppError::internal("Cannot encrypt data"))?;
+                output.write_all(&buffer)?;
+                chunk = next;
+            },
+            None => {
+                encryptor
+                    .encrypt_last_in_place(aad, &mut buffer)
+                    .map_err(|_| AppError::internal("Cannot encrypt data"))?;
+                output.write_all(&buffer)?;
+                break;
+            }
+        }
+    }
+
+    buffer.zeroize();
+    Ok(())
+}
+
+/// Decrypts chunk by chunk and stops on the first one that fails to verify.
+/// `capacity` should be not less than text size, so text copies are not left behind on reallocation.
+pub fn decrypt<R: Read>(key: &[u8], nonce: &[u8], aad: &[u8], input: &mut R, capacity: usize) -> Result<Vec<u8>, AppError> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let decryptor = DecryptorBE32::from_aead(cipher, GenericArray::from_slice(&nonce[..NONCE_PREFIX_SIZE]));
+
+    let mut output = Vec::with_capacity(capacity);
+    let mut buffer = vec![0u8; CHUNK_SIZE + TAG_SIZE];
+    let mut next = vec![0u8; CHUNK_SIZE + TAG_SIZE];
+
+    let result = decrypt_chunks(decryptor, aad, input, &mut buffer, &mut next, &mut output);
+
+    buffer.zeroize();
+    next.zeroize();
+    match result {
+        Ok(_) => Ok(output),
+        Err(e) => {
+            output.zeroize();
+            Err(e)
+        }
+    }
+}
+
+fn decrypt_chunks<R: Read>(
+    mut decryptor: DecryptorBE32<Aes256Gcm>,
+    aad: &[u8],
+    input: &mut R,
+    buffer: &mut Vec<u8>,
+    next: &mut Vec<u8>,
+    output: &mut Vec<u8>) -> Result<(), AppError> {
+
+    // Chunk is the last one when nothing follows it
+    let mut len = read_full(input, buffer)?;
+    loop {
+        let next_len = read_full(input, next)?;
+        if next_len == 0 {
+            break;
+        }
+
+        buffer.truncate(len);
+        decryptor
+            .decrypt_next_in_place(aad, buffer)
+            .map_err(|_| AppError::Damaged)?;
+        output.extend_from_slice(buffer);
+
+        buffer.resize(CHUNK_SIZE + TAG_SIZE, 0);
+        std::mem::swap(buffer, next);
+        len = next_len;
+    }
+
+    buffer.truncate(len);
+    decryptor
+        .decrypt_last_in_place(aad, buffer)
+        .map_err(|_| AppError::Damaged)?;
+    output.extend_from_slice(buffer);
+    Ok(())
+}
+
+/// Reads until buffer is full or input ends
+pub fn read_full<R: Read>(input: &mut R, buf: &mut [u8]) -> Result<usize, AppError> {
+    let mut read = 0;
+    while read < buf.len() {
+        match input.read(&mut buf[read..])? {
+            0 => break,
+            n => read += n
+        }
+    }
+    Ok(read)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY : [u8; 32] = [7u8; 32];
+    const NONCE : [u8; 12] = [3u8; 12];
+
+    fn round_trip(size: usize, chunks: usize) {
+        let data: Vec<u8> = (0..size).map(|i| i as u8).collect();
+        let mut encrypted = Vec::new();
+        encrypt(&KEY, &NONCE, b"header", &data, &mut encrypted).unwrap();
+        assert_eq!(encrypted.len(), size + chunks * TAG_SIZE);
+
+        let decrypted = decrypt(&KEY, &NONCE, b"header", &mut encrypted.as_slice(), size).unwrap();
+        assert_eq!(decrypted, data);
+    }
+
+    #[test]
+    fn empty_text() {
+        round_trip(0, 1);
+    }
+
+    #[test]
+    fn exactly_one_chunk() {
+        round_trip(CHUNK_SIZE, 1);
+    }
+
+    #[test]
+    fn one_byte_over_chunk() {
+        round_trip(CHUNK_SIZE + 1, 2);
+    }
+
+    #[test]
+    fn truncated_stream_is_damaged() {
+        let data = vec![1u8; CHUNK_SIZE + 1];
+        let mut encrypted = Vec::new();
+        encrypt(&KEY, &NONCE, b"header", &data, &mut encrypted).unwrap();
+        encrypted.truncate(CHUNK_SIZE + TAG_SIZE);
+
+        let result = decrypt(&KEY, &NONCE, b"header", &mut encrypted.as_slice(), data.len());
+        assert!(matches!(result, Err(AppError::Damaged)));
+    }
+
+    #[test]
+    fn other_header_is_damaged() {
+        let mut encrypted = Vec::new();
+        encrypt(&KEY, &NONCE, b"header", b"text", &mut encrypted).unwrap();
+
+        let result = decrypt(&KEY, &NONCE, b"other", &mut encrypted.as_slice(), 4);
+        assert!(matches!(result, Err(AppError::Damaged)));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,22 @@
+use zxcvbn::zxcvbn;
+
+/// Estimates how hard password is to guess, from 0 (too guessable) to 4 (very unguessable)
+pub fn score(password: &str) -> u8 {
+    if password.is_empty() {
+        return 0;
+    }
+
+    zxcvbn(password, &[])
+        .map(|e| e.score())
+        .unwrap_or(0)
+}
+
+pub fn describe(score: u8) -> &'static str {
+    match score {
+        0 => "Very weak",
+        1 => "Weak",
+        2 => "Fair",
+        3 => "Good",
+        _ => "Strong"
+    }
+}
\ No newline at end of file
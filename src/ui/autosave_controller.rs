@@ -0,0 +1,55 @@
+use std::time::{Duration, Instant};
+use druid::{Env, Event, EventCtx, TimerToken, Widget};
+use druid::widget::Controller;
+use crate::{AppState, commands};
+
+const CHECK_INTERVAL : Duration = Duration::from_secs(1);
+
+/// Saves changed notes on interval from settings and when window loses focus.
+/// Between saves unsaved text is kept in recovery journal.
+pub struct AutosaveController {
+    timer: TimerToken,
+    last_save: Instant
+}
+
+impl AutosaveController {
+    pub fn new() -> Self {
+        AutosaveController {
+            timer: TimerToken::INVALID,
+            last_save: Instant::now()
+        }
+    }
+
+    fn save(&mut self, ctx: &mut EventCtx, state: &AppState) {
+        if state.tabs.autosave_pending() {
+            ctx.submit_command(commands::AUTOSAVE);
+        }
+        self.last_save = Instant::now();
+    }
+}
+
+impl<W: Widget<AppState>> Controller<AppState, W> for AutosaveController {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut AppState, env: &Env) {
+        match event {
+            Event::WindowConnected => {
+                self.timer = ctx.request_timer(CHECK_INTERVAL);
+            },
+            Event::Timer(token) if *token == self.timer => {
+                let interval = Duration::from_secs(data.config.autosave_seconds);
+                if data.config.autosave_seconds > 0 && self.last_save.elapsed() >= interval {
+                    self.save(ctx, data);
+                } else if data.tabs.journal_pending() {
+                    ctx.submit_command(commands::WRITE_JOURNAL);
+                }
+                self.timer = ctx.request_timer(CHECK_INTERVAL);
+                return;
+            },
+            Event::WindowLostFocus => {
+                self.save(ctx, data);
+            },
+            _ => { }
+        }
+
+        child.event(ctx, event, data, env)
+    }
+}
\ No newline at end of file
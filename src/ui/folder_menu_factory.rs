@@ -0,0 +1,27 @@
+use druid::{Menu, MenuItem};
+use crate::{AppState, commands};
+use crate::ui::context_menu_controller::MenuFactory;
+
+pub struct FolderMenuFactory {
+    folder: String
+}
+
+impl FolderMenuFactory {
+    pub fn new(folder: String) -> Self {
+        FolderMenuFactory {
+            folder
+        }
+    }
+}
+
+impl MenuFactory for FolderMenuFactory {
+    fn make(&self) -> Menu<AppState> {
+        let folder = self.folder.clone();
+        Menu::empty()
+            .entry(MenuItem::new("New folder...")
+                .command(commands::SHOW_NEW_FOLDER.with(self.folder.clone())))
+            .entry(MenuItem::new("Remove folder")
+                .command(commands::REMOVE_FOLDER.with(self.folder.clone()))
+                .enabled_if(move |state: &AppState, _env| state.tabs.can_remove_folder(&folder)))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,57 @@
+use std::time::{Duration, Instant};
+use druid::{Env, Event, EventCtx, TimerToken, Widget, WindowState};
+use druid::widget::Controller;
+use crate::{AppState, commands};
+
+const CHECK_INTERVAL : Duration = Duration::from_secs(1);
+
+pub struct IdleController {
+    timer: TimerToken,
+    last_activity: Instant,
+    minimized: bool
+}
+
+impl IdleController {
+    pub fn new() -> Self {
+        IdleController {
+            timer: TimerToken::INVALID,
+            last_activity: Instant::now(),
+            minimized: false
+        }
+    }
+
+    fn check(&mut self, ctx: &mut EventCtx, state: &AppState) {
+        let minimized = ctx.window().get_window_state() == WindowState::Minimized;
+        if state.config.lock_on_minimize && minimized && !self.minimized {
+            ctx.submit_command(commands::LOCK_ALL);
+        }
+        self.minimized = minimized;
+
+        let timeout = Duration::from_secs(state.config.auto_lock_minutes * 60);
+        if state.config.auto_lock_minutes > 0 && self.last_activity.elapsed() >= timeout {
+            ctx.submit_command(commands::LOCK_ALL);
+            self.last_activity = Instant::now();
+        }
+    }
+}
+
+impl<W: Widget<AppState>> Controller<AppState, W> for IdleController {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut AppState, env: &Env) {
+        match event {
+            Event::WindowConnected => {
+                self.timer = ctx.request_timer(CHECK_INTERVAL);
+            },
+            Event::Timer(token) if *token == self.timer => {
+                self.check(ctx, data);
+                self.timer = ctx.request_timer(CHECK_INTERVAL);
+                return;
+            },
+            Event::KeyDown(_) | Event::MouseDown(_) | Event::MouseMove(_) | Event::Wheel(_) => {
+                self.last_activity = Instant::now();
+            },
+            _ => { }
+        }
+
+        child.event(ctx, event, data, env)
+    }
+}
\ No newline at end of file
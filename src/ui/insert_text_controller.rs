@@ -0,0 +1,21 @@
+use druid::{Env, Event, EventCtx, Widget};
+use druid::widget::{Controller, TextBox};
+use crate::commands;
+
+/// Replaces selection of text box with text sent by `INSERT_TEXT` command
+pub struct InsertTextController;
+
+impl Controller<String, TextBox<String>> for InsertTextController {
+    fn event(&mut self, child: &mut TextBox<String>, ctx: &mut EventCtx, event: &Event, data: &mut String, env: &Env) {
+        if let Event::Command(cmd) = event {
+            if let Some(text) = cmd.get(commands::INSERT_TEXT) {
+                let range = child.text().borrow().selection().range();
+                data.replace_range(range, text.expose());
+                ctx.set_handled();
+                return;
+            }
+        }
+
+        child.event(ctx, event, data, env)
+    }
+}
\ No newline at end of file
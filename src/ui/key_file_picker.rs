@@ -0,0 +1,46 @@
+use druid::{Env, Event, EventCtx, FileDialogOptions, Widget, WidgetExt};
+use druid::widget::{Button, Controller, Flex, Label, SizedBox};
+use crate::commands;
+use crate::state::key_file::KeyFile;
+
+pub fn key_file_picker() -> impl Widget<KeyFile> {
+    Flex::row()
+        .with_flex_child(Label::dynamic(|key_file: &KeyFile, _env| key_file.name())
+            .expand_width(), 1.0)
+        .with_spacer(5.0)
+        .with_child(SizedBox::new(Button::new("Browse...")
+            .on_click(|ctx, key_file: &mut KeyFile, _env| {
+                key_file.begin_selection();
+                let options = FileDialogOptions::new()
+                    .title("Select key file")
+                    .accept_command(commands::KEY_FILE_SELECTED)
+                    .cancel_command(commands::KEY_FILE_CANCELLED);
+                ctx.submit_command(druid::commands::SHOW_OPEN_PANEL.with(options));
+            }))
+            .width(80.0)
+            .height(30.0))
+        .with_spacer(5.0)
+        .with_child(SizedBox::new(Button::new("Clear")
+            .on_click(|_ctx, key_file: &mut KeyFile, _env| key_file.clear()))
+            .width(60.0)
+            .height(30.0))
+        .controller(KeyFilePickerController)
+}
+
+pub struct KeyFilePickerController;
+
+impl<W: Widget<KeyFile>> Controller<KeyFile, W> for KeyFilePickerController {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, key_file: &mut KeyFile, env: &Env) {
+        if let Event::Command(cmd) = event {
+            if key_file.selecting() {
+                if let Some(info) = cmd.get(commands::KEY_FILE_SELECTED) {
+                    key_file.select(info.path());
+                } else if cmd.is(commands::KEY_FILE_CANCELLED) {
+                    key_file.cancel_selection();
+                }
+            }
+        }
+
+        child.event(ctx, event, key_file, env)
+    }
+}
\ No newline at end of file
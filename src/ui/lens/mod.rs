@@ -1,3 +1,5 @@
 pub mod tabs_tate_to_tab_state_lens;
 pub mod tab_state_to_text_lens;
-pub mod tab_state_to_password_lens;
\ No newline at end of file
+pub mod tab_state_to_password_lens;
+pub mod tab_state_to_key_file_lens;
+pub mod new_tab_to_password_shown_lens;
\ No newline at end of file
@@ -0,0 +1,24 @@
+use druid::Lens;
+use crate::state::new_tab::NewTab;
+
+/// Shows or hides both password and its repeat
+pub struct NewTabToPasswordShownLens;
+
+impl NewTabToPasswordShownLens {
+    pub fn new() -> Self {
+        NewTabToPasswordShownLens
+    }
+}
+
+impl Lens<NewTab, bool> for NewTabToPasswordShownLens {
+    fn with<V, F: FnOnce(&bool) -> V>(&self, new_tab: &NewTab, f: F) -> V {
+        f(&new_tab.password.shown())
+    }
+
+    fn with_mut<V, F: FnOnce(&mut bool) -> V>(&self, new_tab: &mut NewTab, f: F) -> V {
+        let mut shown = new_tab.password.shown();
+        let v = f(&mut shown);
+        new_tab.show_password(shown);
+        v
+    }
+}
\ No newline at end of file
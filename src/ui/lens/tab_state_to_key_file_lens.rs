@@ -0,0 +1,30 @@
+use druid::Lens;
+use crate::state::key_file::KeyFile;
+use crate::state::tab_content::TabContent;
+use crate::state::tab::Tab;
+
+pub struct TabStateToKeyFileLens;
+
+impl TabStateToKeyFileLens {
+    pub fn new() -> Self {
+        TabStateToKeyFileLens
+    }
+}
+
+impl Lens<Tab, KeyFile> for TabStateToKeyFileLens {
+    fn with<V, F: FnOnce(&KeyFile) -> V>(&self, tab: &Tab, f: F) -> V {
+        match &tab.content {
+            TabContent::Closed { key_file: Some(key_file), .. } => f(key_file),
+            TabContent::Closed { key_file: None, .. } => panic!("Tab don't require key file"),
+            _ => panic!("Tab already opened")
+        }
+    }
+
+    fn with_mut<V, F: FnOnce(&mut KeyFile) -> V>(&self, tab: &mut Tab, f: F) -> V {
+        match &mut tab.content {
+            TabContent::Closed { key_file: Some(key_file), .. } => f(key_file),
+            TabContent::Closed { key_file: None, .. } => panic!("Tab don't require key file"),
+            _ => panic!("Tab already opened")
+        }
+    }
+}
\ No newline at end of file
@@ -16,7 +16,7 @@ impl Lens<Tab, PasswordText> for TabStateToPasswordLens {
         match &tab.content {
             TabContent::Clear { .. } => panic!("Clear text don't have password"),
             TabContent::Opened { .. } => panic!("Tab already opened"),
-            TabContent::Closed { password } => f(password)
+            TabContent::Closed { password, .. } => f(password)
         }
     }
 
@@ -24,7 +24,7 @@ impl Lens<Tab, PasswordText> for TabStateToPasswordLens {
         match &mut tab.content {
             TabContent::Clear { .. } => panic!("Clear text don't have password"),
             TabContent::Opened { .. } => panic!("Tab already opened"),
-            TabContent::Closed { password } => f(password)
+            TabContent::Closed { password, .. } => f(password)
         }
     }
 }
\ No newline at end of file
@@ -14,7 +14,7 @@ impl Lens<Tab, String> for TabStateToTextLens {
     fn with<V, F: FnOnce(&String) -> V>(&self, tab: &Tab, f: F) -> V {
         match &tab.content {
             TabContent::Clear { text } => f(text),
-            TabContent::Opened { text, .. } => f(text),
+            TabContent::Opened { text, .. } => f(text.expose()),
             TabContent::Closed { .. } => panic!("Tab don't decrypted")
         }
     }
@@ -22,7 +22,7 @@ impl Lens<Tab, String> for TabStateToTextLens {
     fn with_mut<V, F: FnOnce(&mut String) -> V>(&self, tab: &mut Tab, f: F) -> V {
         match &mut tab.content {
             TabContent::Clear { text } => f(text),
-            TabContent::Opened { text, .. } => f(text),
+            TabContent::Opened { text, .. } => text.edit(f),
             TabContent::Closed { .. } => panic!("Tab don't decrypted")
         }
     }
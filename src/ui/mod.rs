@@ -4,4 +4,14 @@ pub mod tab_close_button;
 pub mod lens;
 pub mod copy_cut_paste_controller;
 pub mod context_menu_controller;
-pub mod text_box_menu_factory;
\ No newline at end of file
+pub mod text_box_menu_factory;
+pub mod tab_menu_factory;
+pub mod idle_controller;
+pub mod key_file_picker;
+pub mod insert_text_controller;
+pub mod text_changed_controller;
+pub mod select_tab_controller;
+pub mod notebook_tree;
+pub mod folder_menu_factory;
+pub mod rename_controller;
+pub mod autosave_controller;
\ No newline at end of file
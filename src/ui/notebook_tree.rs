@@ -0,0 +1,69 @@
+use druid::{theme, Widget, WidgetExt};
+use druid::widget::{Container, CrossAxisAlignment, Flex, Label, ViewSwitcher};
+use crate::commands;
+use crate::state::tabs::Tabs;
+use crate::ui::context_menu_controller::ContextMenuController;
+use crate::ui::folder_menu_factory::FolderMenuFactory;
+use crate::ui::tab_menu_factory::TabMenuFactory;
+
+pub const INDENT : f64 = 15.0;
+
+/// Folders of docs with their notes, rebuilt when tabs change
+pub fn notebook_tree() -> impl Widget<Tabs> {
+    ViewSwitcher::new(
+        |tabs: &Tabs, _env| tabs.rev(),
+        |_rev, tabs: &Tabs, _env| -> Box<dyn Widget<Tabs>> {
+            let mut column = Flex::column()
+                .cross_axis_alignment(CrossAxisAlignment::Start);
+            add_folder(&mut column, tabs, String::new());
+            Box::new(column.padding(5.0))
+        })
+}
+
+pub fn folder_label(folder: &str) -> &str {
+    if folder.is_empty() {
+        return "Notes";
+    }
+    folder
+        .rsplit_once('/')
+        .map_or(folder, |(_, name)| name)
+}
+
+pub fn depth(folder: &str) -> usize {
+    if folder.is_empty() {
+        0
+    } else {
+        folder.matches('/').count() + 1
+    }
+}
+
+fn add_folder(column: &mut Flex<Tabs>, tabs: &Tabs, folder: String) {
+    let depth = depth(&folder);
+    let target = folder.clone();
+
+    let mut row = Container::new(Label::new(format!("📁 {}", folder_label(&folder)))
+        .padding((depth as f64 * INDENT, 2.0, 0.0, 2.0))
+        .expand_width());
+    if tabs.folder() == folder {
+        row.set_background(theme::BACKGROUND_LIGHT);
+    }
+    column.add_child(row
+        .on_click(move |_ctx, tabs: &mut Tabs, _env| tabs.set_folder(&target))
+        .controller(ContextMenuController::new(FolderMenuFactory::new(folder.clone()))));
+
+    for subfolder in tabs.subfolders(&folder) {
+        add_folder(column, tabs, subfolder);
+    }
+
+    for key in tabs.folder_keys(&folder) {
+        column.add_child(Label::dynamic(move |tabs: &Tabs, _env| tabs.label(key))
+            .padding(((depth + 1) as f64 * INDENT, 2.0, 0.0, 2.0))
+            .expand_width()
+            .on_click(move |ctx, tabs: &mut Tabs, _env| {
+                let folder = tabs.get(key).folder().to_string();
+                tabs.set_folder(&folder);
+                ctx.submit_command(commands::SELECT_TAB.with(key));
+            })
+            .controller(ContextMenuController::new(TabMenuFactory::new(key))));
+    }
+}
\ No newline at end of file
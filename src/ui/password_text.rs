@@ -1,37 +1,86 @@
 use std::borrow::Cow;
+use std::mem;
 use std::ops::Range;
 use druid::{Data};
 use druid::piet::{TextStorage as PietTextStorage};
 use druid::text::{EditableText, StringCursor, TextStorage};
+use zeroize::Zeroize;
+use crate::secret::Secret;
 
 #[derive(Data, Debug, Clone, PartialEq)]
 pub struct PasswordText {
-    clear: String,
-    hidden: String
+    clear: Secret<String>,
+    hidden: String,
+    // Clear text is displayed instead of password chars
+    shown: bool
 }
 
 const PASSWORD_CHAR: char = '●';
+const MIN_CAPACITY: usize = 64;
 
 impl PasswordText {
     pub fn new() -> Self {
         PasswordText {
-            clear: String::new(),
-            hidden: String::new()
+            clear: Secret::new(String::with_capacity(MIN_CAPACITY)),
+            hidden: String::new(),
+            shown: false
         }
     }
 
     pub fn value(&self) -> &str {
-        &self.clear
+        self.clear.expose()
+    }
+
+    pub fn shown(&self) -> bool {
+        self.shown
+    }
+
+    pub fn set_shown(&mut self, shown: bool) {
+        self.shown = shown;
+    }
+
+    // Text displayed by text box, offsets of all edits are in it
+    fn displayed(&self) -> &String {
+        if self.shown {
+            self.clear.expose()
+        } else {
+            &self.hidden
+        }
     }
 
     pub fn clear(&mut self) {
-        self.clear.replace_range(.., " ");
-        self.clear.clear();
+        self.clear.edit(|clear| clear.zeroize());
         self.hidden.clear();
     }
 
+    pub fn set(&mut self, value: &str) {
+        self.clear();
+        self.clear.edit(|clear| PasswordText::replace(clear, 0..0, value));
+        self.set_hidden();
+    }
+
+    pub fn take(&mut self) -> Secret<String> {
+        self.hidden.clear();
+        self.shown = false;
+        mem::replace(&mut self.clear, Secret::new(String::with_capacity(MIN_CAPACITY)))
+    }
+
+    // Grows buffer manually, so old one is wiped instead of being freed with password inside
+    fn replace(clear: &mut String, range: Range<usize>, new: &str) {
+        let len = clear.len() - range.len() + new.len();
+        if len > clear.capacity() {
+            let mut grown = String::with_capacity(usize::max(len, MIN_CAPACITY) * 2);
+            grown.push_str(clear);
+            clear.zeroize();
+            *clear = grown;
+        }
+
+        clear.replace_range(range, new);
+    }
+
     fn set_hidden(&mut self) {
         let target_len = self.clear
+            .expose()
             .chars()
             .count();
 
@@ -51,11 +100,18 @@ impl PasswordText {
 
 impl EditableText for PasswordText {
     fn cursor(&self, position: usize) -> Option<StringCursor> {
-        <String as EditableText>::cursor(&self.hidden, position)
+        <String as EditableText>::cursor(self.displayed(), position)
     }
 
     fn edit(&mut self, range: Range<usize>, new: impl Into<String>) {
-        let new = new.into();
+        let mut new = new.into();
+        if self.shown {
+            self.clear.edit(|clear| PasswordText::replace(clear, range, &new));
+            new.zeroize();
+            self.set_hidden();
+            return;
+        }
+
         let password_char_len = PASSWORD_CHAR.len_utf8();
         let symbols_start_index = range.start / password_char_len;
         let symbols_end_index = range.end / password_char_len;
@@ -63,7 +119,7 @@ impl EditableText for PasswordText {
         let mut start_index = 0;
         let mut end_index = 0;
 
-        let chars = self.clear.chars();
+        let chars = self.clear.expose().chars();
         for char in chars {
             let len = char.len_utf8();
 
@@ -78,58 +134,60 @@ impl EditableText for PasswordText {
             index += 1;
         }
 
-        self.clear.replace_range(start_index..end_index, &new);
+        self.clear.edit(|clear| PasswordText::replace(clear, start_index..end_index, &new));
+        new.zeroize();
         self.set_hidden();
     }
 
     fn slice(&self, range: Range<usize>) -> Option<Cow<str>> {
-        <String as EditableText>::slice(&self.hidden, range)
+        <String as EditableText>::slice(self.displayed(), range)
     }
 
     fn len(&self) -> usize {
-        <String as EditableText>::len(&self.hidden)
+        <String as EditableText>::len(self.displayed())
     }
 
     fn prev_word_offset(&self, offset: usize) -> Option<usize> {
-        <String as EditableText>::prev_word_offset(&self.hidden, offset)
+        <String as EditableText>::prev_word_offset(self.displayed(), offset)
     }
 
     fn next_word_offset(&self, offset: usize) -> Option<usize> {
-        <String as EditableText>::next_word_offset(&self.hidden, offset)
+        <String as EditableText>::next_word_offset(self.displayed(), offset)
     }
 
     fn prev_grapheme_offset(&self, offset: usize) -> Option<usize> {
-        <String as EditableText>::prev_grapheme_offset(&self.hidden, offset)
+        <String as EditableText>::prev_grapheme_offset(self.displayed(), offset)
     }
 
     fn next_grapheme_offset(&self, offset: usize) -> Option<usize> {
-        <String as EditableText>::next_grapheme_offset(&self.hidden, offset)
+        <String as EditableText>::next_grapheme_offset(self.displayed(), offset)
     }
 
     fn prev_codepoint_offset(&self, offset: usize) -> Option<usize> {
-        <String as EditableText>::prev_codepoint_offset(&self.hidden, offset)
+        <String as EditableText>::prev_codepoint_offset(self.displayed(), offset)
     }
 
     fn next_codepoint_offset(&self, offset: usize) -> Option<usize> {
-        <String as EditableText>::next_codepoint_offset(&self.hidden, offset)
+        <String as EditableText>::next_codepoint_offset(self.displayed(), offset)
     }
 
     fn preceding_line_break(&self, offset: usize) -> usize {
-        <String as EditableText>::preceding_line_break(&self.hidden, offset)
+        <String as EditableText>::preceding_line_break(self.displayed(), offset)
     }
 
     fn next_line_break(&self, offset: usize) -> usize {
-        <String as EditableText>::next_line_break(&self.hidden, offset)
+        <String as EditableText>::next_line_break(self.displayed(), offset)
     }
 
     fn is_empty(&self) -> bool {
-        <String as EditableText>::is_empty(&self.hidden)
+        <String as EditableText>::is_empty(self.displayed())
     }
 
     fn from_str(s: &str) -> Self {
         let mut text = PasswordText {
-            clear: <String as EditableText>::from_str(s),
-            hidden: String::new()
+            clear: Secret::new(<String as EditableText>::from_str(s)),
+            hidden: String::new(),
+            shown: false
         };
         text.set_hidden();
         text
@@ -138,7 +196,7 @@ impl EditableText for PasswordText {
 
 impl PietTextStorage for PasswordText {
     fn as_str(&self) -> &str {
-        &self.hidden
+        self.displayed()
     }
 }
 
@@ -0,0 +1,28 @@
+use druid::{Data, Env, Event, EventCtx, Widget};
+use druid::widget::Controller;
+use crate::commands;
+
+/// Opens rename window on double click of tab label
+pub struct RenameController {
+    key: u64
+}
+
+impl RenameController {
+    pub fn new(key: u64) -> Self {
+        RenameController {
+            key
+        }
+    }
+}
+
+impl<T: Data, W: Widget<T>> Controller<T, W> for RenameController {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        match event {
+            Event::MouseDown(ref mouse) if mouse.button.is_left() && mouse.count == 2 => {
+                ctx.submit_command(commands::SHOW_RENAME_TAB.with(self.key));
+                ctx.set_handled();
+            }
+            _ => child.event(ctx, event, data, env),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,25 @@
+use druid::{Env, Event, EventCtx, Widget};
+use druid::widget::{self, Controller};
+use crate::commands;
+use crate::state::tabs::Tabs;
+use crate::ui::tabs_dynamic_policy::TabsDynamicPolicy;
+
+/// Switches tab strip to tab chosen outside of it, e.g. in notebook tree
+pub struct SelectTabController;
+
+impl Controller<Tabs, widget::Tabs<TabsDynamicPolicy>> for SelectTabController {
+    fn event(&mut self, child: &mut widget::Tabs<TabsDynamicPolicy>, ctx: &mut EventCtx, event: &Event, data: &mut Tabs, env: &Env) {
+        if let Event::Command(cmd) = event {
+            if let Some(key) = cmd.get(commands::SELECT_TAB) {
+                if let Some(index) = data.keys().iter().position(|k| k == key) {
+                    child.set_tab_index(index);
+                    ctx.request_layout();
+                }
+                ctx.set_handled();
+                return;
+            }
+        }
+
+        child.event(ctx, event, data, env)
+    }
+}
\ No newline at end of file
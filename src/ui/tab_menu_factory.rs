@@ -0,0 +1,105 @@
+use druid::{Menu, MenuItem};
+use crate::{AppState, commands};
+use crate::state::index::LabelColor;
+use crate::ui::context_menu_controller::MenuFactory;
+
+pub struct TabMenuFactory {
+    key: u64
+}
+
+impl TabMenuFactory {
+    pub fn new(key: u64) -> Self {
+        TabMenuFactory {
+            key
+        }
+    }
+}
+
+impl MenuFactory for TabMenuFactory {
+    fn make(&self) -> Menu<AppState> {
+        let key = self.key;
+        // Last menu is kept and re-evaluated after its tab is closed, so every item checks that tab exists
+        let mut colors = Menu::new("Color")
+            .entry(MenuItem::new("None")
+                .command(commands::SET_TAB_COLOR.with((key, None)))
+                .selected_if(move |state: &AppState, _env| state.tabs.color(key).is_none())
+                .enabled_if(move |state: &AppState, _env| state.tabs.contains(key)));
+        for color in LabelColor::ALL {
+            colors = colors.entry(MenuItem::new(color.name())
+                .command(commands::SET_TAB_COLOR.with((key, Some(color))))
+                .selected_if(move |state: &AppState, _env| state.tabs.color(key) == Some(color))
+                .enabled_if(move |state: &AppState, _env| state.tabs.contains(key)));
+        }
+
+        Menu::empty()
+            .entry(MenuItem::new("Rename...")
+                .command(commands::SHOW_RENAME_TAB.with(key))
+                .enabled_if(move |state: &AppState, _env| state.tabs.contains(key) && {
+                    let tab = state.tabs.get(key);
+                    tab.unlocked() || !tab.hidden_name()
+                }))
+            .separator()
+            .entry(MenuItem::new("Pin")
+                .command(commands::PIN_TAB.with(key))
+                .enabled_if(move |state: &AppState, _env| state.tabs.contains(key) && !state.tabs.get(key).meta.pinned))
+            .entry(MenuItem::new("Unpin")
+                .command(commands::UNPIN_TAB.with(key))
+                .enabled_if(move |state: &AppState, _env| state.tabs.contains(key) && state.tabs.get(key).meta.pinned))
+            .entry(MenuItem::new("Move left")
+                .command(commands::MOVE_TAB_LEFT.with(key))
+                .enabled_if(move |state: &AppState, _env| state.tabs.contains(key) && state.tabs.can_move_tab(key, false)))
+            .entry(MenuItem::new("Move right")
+                .command(commands::MOVE_TAB_RIGHT.with(key))
+                .enabled_if(move |state: &AppState, _env| state.tabs.contains(key) && state.tabs.can_move_tab(key, true)))
+            .entry(colors)
+            .entry(MenuItem::new("Move to folder...")
+                .command(commands::SHOW_MOVE_TO_FOLDER.with(key))
+                .enabled_if(move |state: &AppState, _env| {
+                    // There is somewhere to move only if note is in subfolder or subfolders exist
+                    state.tabs.contains(key) && (!state.tabs.folders().is_empty() || !state.tabs.get(key).folder().is_empty())
+                }))
+            .separator()
+            .entry(MenuItem::new("Lock")
+                .command(commands::LOCK_TAB.with(key))
+                .enabled_if(move |state: &AppState, _env| state.tabs.contains(key) && state.tabs.get(key).unlocked()))
+            .separator()
+            .entry(MenuItem::new("Protect...")
+                .command(commands::SHOW_PROTECT.with(key))
+                .enabled_if(move |state: &AppState, _env| state.tabs.contains(key) && state.tabs.get(key).clear()))
+            .entry(MenuItem::new("Remove protection...")
+                .command(commands::SHOW_REMOVE_PROTECTION.with(key))
+                .enabled_if(move |state: &AppState, _env| state.tabs.contains(key) && state.tabs.get(key).unlocked()))
+            .entry(MenuItem::new("Change password...")
+                .command(commands::SHOW_CHANGE_PASSWORD.with(key))
+                .enabled_if(move |state: &AppState, _env| state.tabs.contains(key) && state.tabs.get(key).unlocked()))
+            .entry(MenuItem::new("New recovery code")
+                .command(commands::NEW_RECOVERY_CODE.with(key))
+                .enabled_if(move |state: &AppState, _env| state.tabs.contains(key) && state.tabs.get(key).unlocked()))
+            .entry(MenuItem::new("Hide name on disk")
+                .command(commands::HIDE_NAME.with(key))
+                .enabled_if(move |state: &AppState, _env| state.tabs.contains(key) && {
+                    let tab = state.tabs.get(key);
+                    tab.unlocked() && !tab.hidden_name()
+                }))
+            .entry(MenuItem::new("Show name on disk")
+                .command(commands::SHOW_NAME.with(key))
+                .enabled_if(move |state: &AppState, _env| state.tabs.contains(key) && {
+                    let tab = state.tabs.get(key);
+                    tab.unlocked() && tab.hidden_name()
+                }))
+            .separator()
+            .entry(MenuItem::new("History...")
+                .command(commands::SHOW_HISTORY.with(key))
+                .enabled_if(move |state: &AppState, _env| state.tabs.contains(key) && state.tabs.get(key).opened()))
+            .entry(MenuItem::new("Export...")
+                .command(commands::SHOW_EXPORT.with(key))
+                .enabled_if(move |state: &AppState, _env| state.tabs.contains(key) && state.tabs.get(key).opened()))
+            .separator()
+            .entry(MenuItem::new("Add to vault")
+                .command(commands::ADD_TO_VAULT.with(key))
+                .enabled_if(move |state: &AppState, _env| state.tabs.contains(key) && {
+                    let tab = state.tabs.get(key);
+                    tab.unlocked() && !tab.in_vault() && state.vault.unlocked()
+                }))
+    }
+}
\ No newline at end of file
@@ -1,8 +1,11 @@
-use druid::{FontDescriptor, WidgetExt, Data, FontFamily, Widget};
+use druid::{Color, FontDescriptor, WidgetExt, Data, FontFamily, theme, Widget, WidgetId};
 use druid::widget::{Button, Flex, Label, MainAxisAlignment, SizedBox, TabInfo, TabsPolicy, TextBox, ViewSwitcher};
-use crate::error::AppError;
+use crate::commands;
+use crate::state::index::LabelColor;
 use crate::state::tab::Tab;
 use crate::state::tabs::Tabs;
+use crate::ui::key_file_picker::key_file_picker;
+use crate::ui::lens::tab_state_to_key_file_lens::TabStateToKeyFileLens;
 use crate::ui::lens::tab_state_to_password_lens::TabStateToPasswordLens;
 use crate::ui::lens::tab_state_to_text_lens::TabStateToTextLens;
 use crate::ui::lens::tabs_tate_to_tab_state_lens::TabsStateToTabStateLens;
@@ -10,6 +13,10 @@ use crate::ui::tab_close_button::close_button;
 use crate::{AppState, windows};
 use crate::ui::context_menu_controller::ContextMenuController;
 use crate::ui::copy_cut_paste_controller::CopyCutPasteController;
+use crate::ui::insert_text_controller::InsertTextController;
+use crate::ui::rename_controller::RenameController;
+use crate::ui::tab_menu_factory::TabMenuFactory;
+use crate::ui::text_changed_controller::TextChangedController;
 use crate::ui::text_box_menu_factory::TextBoxMenuFactory;
 
 #[derive(Clone, Data)]
@@ -20,34 +27,44 @@ impl TabsDynamicPolicy {
         let font = FontDescriptor::new(FontFamily::MONOSPACE)
             .with_size(14.0);
 
+        let id = WidgetId::next();
         let text_box = TextBox::multiline()
             .with_line_wrapping(false)
             .with_font(font)
+            .controller(InsertTextController)
+            .with_id(id)
             .expand()
-            .controller(ContextMenuController::new(TextBoxMenuFactory))
+            .controller(ContextMenuController::new(TextBoxMenuFactory::new(id)))
             .controller(CopyCutPasteController)
-            .lens(TabStateToTextLens::new());
+            .lens(TabStateToTextLens::new())
+            .controller(TextChangedController::new());
 
         Box::new(text_box)
     }
 
-    fn password_box() -> Box<dyn Widget<Tab>> {
-        let password_box = Flex::row()
+    fn password_box(key_file: bool) -> Box<dyn Widget<Tab>> {
+        let mut password_box = Flex::column()
             .main_axis_alignment(MainAxisAlignment::Center)
-            .with_child(TextBox::new()
-                .lens(TabStateToPasswordLens::new()))
-            .with_spacer(5.0)
-            .with_child(SizedBox::new(Button::new("Ok")
-                .on_click(|ctx, tab: &mut Tab, _env| {
-                    if let Err(AppError::InvalidPassword) = tab.open() {
-                        ctx.new_window(windows::information_window::new("Invalid password"));
-                    }
-                }))
-                .width(50.0)
-                .height(30.0))
-            .expand();
+            .with_child(Flex::row()
+                .main_axis_alignment(MainAxisAlignment::Center)
+                .with_child(TextBox::new()
+                    .lens(TabStateToPasswordLens::new()))
+                .with_spacer(5.0)
+                .with_child(SizedBox::new(Button::new("Ok")
+                    .on_click(|ctx, tab: &mut Tab, _env| {
+                        ctx.submit_command(commands::OPEN_TAB.with(tab.id));
+                    }))
+                    .width(50.0)
+                    .height(30.0)));
 
-        Box::new(password_box)
+        if key_file {
+            password_box.add_spacer(5.0);
+            password_box.add_child(SizedBox::new(key_file_picker()
+                .lens(TabStateToKeyFileLens::new()))
+                .width(300.0));
+        }
+
+        Box::new(password_box.expand())
     }
 }
 
@@ -68,16 +85,16 @@ impl TabsPolicy for TabsDynamicPolicy {
 
     fn tab_info(&self, key: u64, data: &Tabs) -> TabInfo<Tabs> {
         let tab = data.get(key);
-        TabInfo::new(tab.name.clone(), false)
+        TabInfo::new(tab.label().to_string(), false)
     }
 
     fn tab_body(&self, key: u64, _tabs: &Tabs) -> Self::BodyWidget {
-        let switcher = ViewSwitcher::<Tab, bool>::new(
-            |tab, _env| -> bool { tab.opened() },
+        let switcher = ViewSwitcher::<Tab, (bool, bool)>::new(
+            |tab, _env| -> (bool, bool) { (tab.opened(), tab.requires_key_file()) },
             move |val, _tab, _env| -> Box<dyn Widget<Tab>> {
                 match val {
-                    true => TabsDynamicPolicy::text_box(),
-                    false => TabsDynamicPolicy::password_box()
+                    (true, _) => TabsDynamicPolicy::text_box(),
+                    (false, key_file) => TabsDynamicPolicy::password_box(*key_file)
                 }
             })
             .lens(TabsStateToTabStateLens::new(key));
@@ -86,16 +103,37 @@ impl TabsPolicy for TabsDynamicPolicy {
             .with_flex_child(switcher, 1.0)
     }
 
-    fn tab_label(&self, key: u64, info: TabInfo<Tabs>, _tabs: &Tabs) -> Self::LabelWidget {
+    fn tab_label(&self, key: u64, _info: TabInfo<Tabs>, _tabs: &Tabs) -> Self::LabelWidget {
+        // Hidden name is known only while tab is unlocked
         Flex::row()
-            .with_child(Label::new(info.name))
+            .with_child(Label::dynamic(move |tabs: &Tabs, _env| tabs.label(key))
+                .env_scope(move |env, tabs: &Tabs| {
+                    if let Some(color) = tabs.color(key) {
+                        env.set(theme::TEXT_COLOR, label_color(color));
+                    }
+                })
+                .controller(ContextMenuController::new(TabMenuFactory::new(key)))
+                .controller(RenameController::new(key)))
             .with_child(close_button()
                 .on_click(move |ctx, _tabs: &mut Tabs, _env| {
                     ctx.new_window(windows::dialog_window::new(
-                        "Are you sure?",
-                        move |_ctx, state: &mut AppState, _env| {
-                            state.tabs.remove(key).expect("Unexpected error")
+                        "Move note to trash?",
+                        move |ctx, state: &mut AppState, _env| {
+                            if let Err(e) = state.remove_tab(key) {
+                                ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                            }
                         }));
                 }))
     }
+}
+
+fn label_color(color: LabelColor) -> Color {
+    match color {
+        LabelColor::Red => Color::rgb8(0xe5, 0x48, 0x4d),
+        LabelColor::Orange => Color::rgb8(0xf5, 0x8f, 0x3b),
+        LabelColor::Yellow => Color::rgb8(0xe8, 0xc5, 0x3a),
+        LabelColor::Green => Color::rgb8(0x4c, 0xb7, 0x5c),
+        LabelColor::Blue => Color::rgb8(0x4a, 0x90, 0xe2),
+        LabelColor::Purple => Color::rgb8(0x9b, 0x6b, 0xd6)
+    }
 }
\ No newline at end of file
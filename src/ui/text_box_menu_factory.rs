@@ -1,8 +1,19 @@
-use druid::{commands, Menu, MenuItem};
+use druid::{commands, Menu, MenuItem, WidgetId};
 use crate::AppState;
 use crate::ui::context_menu_controller::MenuFactory;
+use crate::windows::generator_window::Output;
 
-pub struct TextBoxMenuFactory;
+pub struct TextBoxMenuFactory {
+    id: WidgetId
+}
+
+impl TextBoxMenuFactory {
+    pub fn new(id: WidgetId) -> Self {
+        TextBoxMenuFactory {
+            id
+        }
+    }
+}
 
 impl MenuFactory for TextBoxMenuFactory {
     fn make(&self) -> Menu<AppState> {
@@ -13,5 +24,8 @@ impl MenuFactory for TextBoxMenuFactory {
                 .command(commands::CUT))
             .entry(MenuItem::new("Paste")
                 .command(commands::PASTE))
+            .separator()
+            .entry(MenuItem::new("Insert generated password...")
+                .command(crate::commands::SHOW_GENERATOR.with(Output::TextBox(self.id))))
     }
 }
\ No newline at end of file
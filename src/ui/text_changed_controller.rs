@@ -0,0 +1,39 @@
+use druid::{Env, Event, EventCtx, UpdateCtx, Widget};
+use druid::widget::Controller;
+use crate::commands;
+use crate::state::tab::Tab;
+
+/// Tells that text of tab is edited by user, so modification time is updated outside of the widget.
+/// Text replaced by reload or unlock is not reported, it is changed outside of widget events.
+#[derive(Default)]
+pub struct TextChangedController {
+    // Event that can edit text went to the text box since the last update
+    editing: bool
+}
+
+impl TextChangedController {
+    pub fn new() -> Self {
+        TextChangedController {
+            editing: false
+        }
+    }
+}
+
+impl<W: Widget<Tab>> Controller<Tab, W> for TextChangedController {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut Tab, env: &Env) {
+        if matches!(event, Event::KeyDown(_) | Event::Paste(_) | Event::ImeStateChange | Event::Command(_)) {
+            self.editing = true;
+        }
+        child.event(ctx, event, data, env)
+    }
+
+    fn update(&mut self, child: &mut W, ctx: &mut UpdateCtx, old_data: &Tab, data: &Tab, env: &Env) {
+        // Text is compared only after edit events, the same comparison is done by the text box itself
+        if self.editing && old_data.id == data.id && old_data.text() != data.text() {
+            ctx.submit_command(commands::TEXT_CHANGED.with(data.id));
+        }
+        self.editing = false;
+
+        child.update(ctx, old_data, data, env)
+    }
+}
\ No newline at end of file
@@ -4,7 +4,7 @@ use crate::{AppState, windows};
 use crate::windows::primary_screen_center;
 
 pub fn new() -> WindowDesc<AppState> {
-    let size = (400.0, 200.0);
+    let size = (400.0, 280.0);
     WindowDesc::new(ui())
         .title("Backup")
         .window_size(size)
@@ -56,6 +56,19 @@ pub fn ui() -> impl Widget<AppState> {
                 .lens(lens!(AppState, config.backup_file))
                 .expand_width(), 1.0))
         .with_spacer(10.0)
+        .with_child(Flex::row()
+            .with_flex_child(Label::new("Encrypt backup with passphrase").expand_width(), 1.0)
+            .with_spacer(10.0)
+            .with_child(Switch::new()
+                .lens(lens!(AppState, config.encrypt_backup))))
+        .with_spacer(10.0)
+        .with_child(Flex::row()
+            .with_child(SizedBox::new(Label::new("Passphrase")))
+            .with_spacer(10.0)
+            .with_flex_child(TextBox::new()
+                .lens(lens!(AppState, backup_passphrase))
+                .expand_width(), 1.0))
+        .with_spacer(10.0)
         .with_child(Flex::row()
             .with_child(SizedBox::new(Button::new("Ok")
                 .on_click(|cx, state: &mut AppState, _env| {
@@ -0,0 +1,79 @@
+use druid::{Env, Event, EventCtx, FileDialogOptions, FileSpec, lens, Widget, WidgetExt, WindowDesc};
+use druid::widget::{Button, Controller, Flex, Label, MainAxisAlignment, Padding, SizedBox, TextBox};
+use crate::{AppState, commands, export, windows};
+use crate::windows::primary_screen_center;
+
+pub fn new(key: u64) -> WindowDesc<AppState> {
+    let size = (400.0, 120.0);
+    WindowDesc::new(ui(key))
+        .title("Export")
+        .window_size(size)
+        .resizable(false)
+        .set_position(primary_screen_center(size))
+}
+
+const LABEL_WIDTH : f64 = 120.0;
+const AGE : FileSpec = FileSpec::new("Age encrypted file", &[export::EXTENSION]);
+
+fn ui(key: u64) -> impl Widget<AppState> {
+    Padding::new(5.0, Flex::column()
+        .with_child(Flex::row()
+            .with_child(SizedBox::new(Label::new("Passphrase:"))
+                .width(LABEL_WIDTH))
+            .with_spacer(5.0)
+            .with_flex_child(TextBox::new()
+                .expand_width()
+                .lens(lens!(AppState, password_form.password)), 1.0))
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .with_child(SizedBox::new(Label::new("Repeat passphrase:"))
+                .width(LABEL_WIDTH))
+            .with_spacer(5.0)
+            .with_flex_child(TextBox::new()
+                .expand_width()
+                .lens(lens!(AppState, password_form.repeat)), 1.0))
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .main_axis_alignment(MainAxisAlignment::End)
+            .with_child(SizedBox::new(Button::new("Export...")
+                .on_click(move |ctx, state: &mut AppState, _env| {
+                    let name = format!("{}.{}", state.tabs.get(key).name, export::EXTENSION);
+                    let options = FileDialogOptions::new()
+                        .title("Export")
+                        .allowed_types(vec![AGE])
+                        .default_type(AGE)
+                        .default_name(name)
+                        .accept_command(commands::EXPORT_FILE_SELECTED);
+                    ctx.submit_command(druid::commands::SHOW_SAVE_PANEL.with(options));
+                })
+                .disabled_if(move |state: &AppState, _env| !state.tabs.contains(key)))
+                .width(90.0)
+                .height(30.0))
+            .expand_width()))
+        .controller(ExportController { key })
+}
+
+struct ExportController {
+    key: u64
+}
+
+impl<W: Widget<AppState>> Controller<AppState, W> for ExportController {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, state: &mut AppState, env: &Env) {
+        if let Event::Command(cmd) = event {
+            if let Some(info) = cmd.get(commands::EXPORT_FILE_SELECTED) {
+                // Note may be removed while save dialog is open
+                if !state.tabs.contains(self.key) {
+                    ctx.new_window(windows::information_window::new("Error: Note was removed"));
+                } else if let Err(e) = state.export_tab(self.key, info.path()) {
+                    ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                }
+                ctx
+                    .window()
+                    .close();
+                return;
+            }
+        }
+
+        child.event(ctx, event, state, env)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,110 @@
+use druid::{FontDescriptor, FontFamily, lens, Widget, WidgetExt, WidgetId, WindowDesc};
+use druid::text::ParseFormatter;
+use druid::widget::{Button, Checkbox, Flex, Label, MainAxisAlignment, Padding, SizedBox, Switch, TextBox};
+use crate::{AppState, commands, windows};
+use crate::windows::primary_screen_center;
+
+/// Where generated password goes
+#[derive(Clone, Copy)]
+pub enum Output {
+    NewTab,
+    TextBox(WidgetId)
+}
+
+pub fn new(output: Output) -> WindowDesc<AppState> {
+    let size = (420.0, 230.0);
+    WindowDesc::new(ui(output))
+        .title("Generate password")
+        .window_size(size)
+        .resizable(false)
+        .set_position(primary_screen_center(size))
+}
+
+const LABEL_WIDTH : f64 = 90.0;
+
+fn ui(output: Output) -> impl Widget<AppState> {
+    let font = FontDescriptor::new(FontFamily::MONOSPACE)
+        .with_size(14.0);
+
+    Padding::new(5.0, Flex::column()
+        .with_child(Flex::row()
+            .with_child(SizedBox::new(Label::new("Passphrase:"))
+                .width(LABEL_WIDTH))
+            .with_spacer(5.0)
+            .with_child(Switch::new()
+                .lens(lens!(AppState, generator.passphrase))))
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .with_child(SizedBox::new(Label::new("Length:"))
+                .width(LABEL_WIDTH))
+            .with_spacer(5.0)
+            .with_child(SizedBox::new(TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .update_data_while_editing(true)
+                .lens(lens!(AppState, generator.length)))
+                .width(60.0))
+            .with_spacer(5.0)
+            .with_child(Checkbox::new("a-z")
+                .lens(lens!(AppState, generator.lowercase)))
+            .with_spacer(5.0)
+            .with_child(Checkbox::new("A-Z")
+                .lens(lens!(AppState, generator.uppercase)))
+            .with_spacer(5.0)
+            .with_child(Checkbox::new("0-9")
+                .lens(lens!(AppState, generator.digits)))
+            .with_spacer(5.0)
+            .with_child(Checkbox::new("!@#")
+                .lens(lens!(AppState, generator.symbols)))
+            .disabled_if(|state: &AppState, _env| state.generator.passphrase))
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .with_child(SizedBox::new(Label::new("Words:"))
+                .width(LABEL_WIDTH))
+            .with_spacer(5.0)
+            .with_child(SizedBox::new(TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .update_data_while_editing(true)
+                .lens(lens!(AppState, generator.words)))
+                .width(60.0))
+            .with_spacer(5.0)
+            .with_child(Label::new("Separator:"))
+            .with_spacer(5.0)
+            .with_child(SizedBox::new(TextBox::new()
+                .lens(lens!(AppState, generator.separator)))
+                .width(40.0))
+            .disabled_if(|state: &AppState, _env| !state.generator.passphrase))
+        .with_spacer(10.0)
+        .with_child(Label::dynamic(|state: &AppState, _env| state.generator.value().to_string())
+            .with_font(font)
+            .expand_width())
+        .with_spacer(5.0)
+        .with_child(Label::dynamic(|state: &AppState, _env| format!("~{:.0} bits", state.generator.entropy()))
+            .expand_width())
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .main_axis_alignment(MainAxisAlignment::End)
+            .with_child(SizedBox::new(Button::new("Generate")
+                .on_click(|ctx, state: &mut AppState, _env| {
+                    if let Err(e) = state.generator.generate() {
+                        ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                    }
+                })
+                .disabled_if(|state: &AppState, _env| !state.generator.can_generate()))
+                .width(90.0)
+                .height(30.0))
+            .with_spacer(5.0)
+            .with_child(SizedBox::new(Button::new("Use")
+                .on_click(move |ctx, state: &mut AppState, _env| {
+                    match output {
+                        Output::NewTab => state.use_generated_password(),
+                        Output::TextBox(id) => ctx.submit_command(commands::INSERT_TEXT.with(state.generator.take()).to(id))
+                    }
+                    ctx
+                        .window()
+                        .close();
+                })
+                .disabled_if(|state: &AppState, _env| state.generator.value().is_empty()))
+                .width(70.0)
+                .height(30.0))
+            .expand_width()))
+}
\ No newline at end of file
@@ -0,0 +1,91 @@
+use chrono::Local;
+use druid::{lens, FontDescriptor, FontFamily, Widget, WidgetExt, WindowDesc};
+use druid::im::Vector;
+use druid::widget::{Button, CrossAxisAlignment, Flex, Label, LineBreaking, MainAxisAlignment, Padding, RadioGroup, Scroll, SizedBox, ViewSwitcher};
+use crate::{AppState, windows};
+use crate::state::history::{self, Version};
+use crate::windows::primary_screen_center;
+
+pub fn new(key: u64) -> WindowDesc<AppState> {
+    let size = (700.0, 450.0);
+    WindowDesc::new(ui(key))
+        .title("History")
+        .window_size(size)
+        .set_position(primary_screen_center(size))
+}
+
+fn ui(key: u64) -> impl Widget<AppState> {
+    let versions = ViewSwitcher::new(
+        |state: &AppState, _env| state.versions.clone(),
+        |versions: &Vector<Version>, _state, _env| -> Box<dyn Widget<AppState>> {
+            if versions.is_empty() {
+                return Box::new(Label::new("No versions yet"));
+            }
+
+            let items: Vec<(String, Option<String>)> = versions
+                .iter()
+                .map(|v| {
+                    let created = v.created
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string();
+                    (created, Some(v.id.clone()))
+                })
+                .collect();
+
+            Box::new(RadioGroup::column(items)
+                .lens(lens!(AppState, selected_version)))
+        });
+
+    // Rebuilt when note is edited, so diff is always against the current text
+    let diff = ViewSwitcher::new(
+        move |state: &AppState, _env| {
+            let modified = state.tabs.contains(key).then(|| state.tabs.get(key).meta.modified);
+            (state.selected_version.clone(), modified)
+        },
+        move |(selected, modified), state: &AppState, _env| -> Box<dyn Widget<AppState>> {
+            if modified.is_none() {
+                return Box::new(Label::new("Note was removed"));
+            }
+
+            let tab = state.tabs.get(key);
+            let text = match selected {
+                Some(id) => match tab.version_text(id) {
+                    Ok(version) => history::diff(version.expose(), tab.text().unwrap_or_default()),
+                    Err(e) => format!("Error: {}", e)
+                },
+                None => String::new()
+            };
+
+            let font = FontDescriptor::new(FontFamily::MONOSPACE)
+                .with_size(13.0);
+            Box::new(Label::new(text)
+                .with_font(font)
+                .with_line_break_mode(LineBreaking::Overflow))
+        });
+
+    Padding::new(5.0, Flex::column()
+        .with_flex_child(Flex::row()
+            .cross_axis_alignment(CrossAxisAlignment::Start)
+            .with_child(SizedBox::new(Scroll::new(versions)
+                .vertical())
+                .width(180.0)
+                .expand_height())
+            .with_spacer(5.0)
+            .with_flex_child(Scroll::new(diff)
+                .expand(), 1.0), 1.0)
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .main_axis_alignment(MainAxisAlignment::SpaceBetween)
+            .with_child(Label::new("- selected version, + current text"))
+            .with_child(SizedBox::new(Button::new("Restore")
+                .on_click(move |ctx, state: &mut AppState, _env| {
+                    if let Err(e) = state.restore_version(key) {
+                        ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                    }
+                })
+                .disabled_if(move |state: &AppState, _env| state.selected_version.is_none() || !state.tabs.contains(key)))
+                .width(80.0)
+                .height(30.0))
+            .expand_width()))
+}
\ No newline at end of file
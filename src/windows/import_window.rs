@@ -0,0 +1,79 @@
+use druid::{Env, Event, EventCtx, FileDialogOptions, FileSpec, lens, Widget, WidgetExt, WindowDesc};
+use druid::widget::{Button, Controller, Flex, Label, MainAxisAlignment, Padding, SizedBox, TextBox};
+use crate::{AppState, commands, export, windows};
+use crate::windows::primary_screen_center;
+
+pub fn new() -> WindowDesc<AppState> {
+    let size = (400.0, 155.0);
+    WindowDesc::new(ui())
+        .title("Import")
+        .window_size(size)
+        .resizable(false)
+        .set_position(primary_screen_center(size))
+}
+
+const LABEL_WIDTH : f64 = 120.0;
+const AGE : FileSpec = FileSpec::new("Age encrypted file", &[export::EXTENSION]);
+
+fn ui() -> impl Widget<AppState> {
+    Padding::new(5.0, Flex::column()
+        .with_child(Flex::row()
+            .with_child(SizedBox::new(Label::new("File passphrase:"))
+                .width(LABEL_WIDTH))
+            .with_spacer(5.0)
+            .with_flex_child(TextBox::new()
+                .expand_width()
+                .lens(lens!(AppState, password_form.current)), 1.0))
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .with_child(SizedBox::new(Label::new("New password:"))
+                .width(LABEL_WIDTH))
+            .with_spacer(5.0)
+            .with_flex_child(TextBox::new()
+                .expand_width()
+                .lens(lens!(AppState, password_form.password)), 1.0))
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .with_child(SizedBox::new(Label::new("Repeat password:"))
+                .width(LABEL_WIDTH))
+            .with_spacer(5.0)
+            .with_flex_child(TextBox::new()
+                .expand_width()
+                .lens(lens!(AppState, password_form.repeat)), 1.0))
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .main_axis_alignment(MainAxisAlignment::End)
+            .with_child(SizedBox::new(Button::new("Import...")
+                .on_click(|ctx, _state: &mut AppState, _env| {
+                    let options = FileDialogOptions::new()
+                        .title("Import")
+                        .allowed_types(vec![AGE])
+                        .accept_command(commands::IMPORT_FILE_SELECTED);
+                    ctx.submit_command(druid::commands::SHOW_OPEN_PANEL.with(options));
+                }))
+                .width(90.0)
+                .height(30.0))
+            .expand_width()))
+        .controller(ImportController)
+}
+
+struct ImportController;
+
+impl<W: Widget<AppState>> Controller<AppState, W> for ImportController {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, state: &mut AppState, env: &Env) {
+        if let Event::Command(cmd) = event {
+            if let Some(info) = cmd.get(commands::IMPORT_FILE_SELECTED) {
+                match state.import_tab(info.path()) {
+                    Ok(code) => ctx.new_window(windows::recovery_code_window::new(&code)),
+                    Err(e) => ctx.new_window(windows::information_window::new(format!("Error: {}", e)))
+                };
+                ctx
+                    .window()
+                    .close();
+                return;
+            }
+        }
+
+        child.event(ctx, event, state, env)
+    }
+}
\ No newline at end of file
@@ -1,7 +1,11 @@
 use druid::{commands, Env, lens, Menu, MenuItem, Widget, WidgetExt, WindowDesc, WindowId};
-use druid::widget::Tabs;
+use druid::widget::{Scroll, Split, Tabs};
 use druid_shell::RawMods;
 use crate::*;
+use crate::ui::autosave_controller::AutosaveController;
+use crate::ui::idle_controller::IdleController;
+use crate::ui::notebook_tree::notebook_tree;
+use crate::ui::select_tab_controller::SelectTabController;
 use crate::ui::tabs_dynamic_policy::TabsDynamicPolicy;
 
 pub fn new() -> WindowDesc<AppState> {
@@ -14,8 +18,18 @@ pub fn new() -> WindowDesc<AppState> {
 }
 
 fn ui() -> impl Widget<AppState> {
-    Tabs::for_policy(TabsDynamicPolicy)
+    let tree = Scroll::new(notebook_tree())
+        .vertical();
+    let tabs = Tabs::for_policy(TabsDynamicPolicy)
+        .controller(SelectTabController);
+
+    Split::columns(tree, tabs)
+        .split_point(0.15)
+        .min_size(150.0, 400.0)
+        .draggable(true)
         .lens(lens!(AppState, tabs))
+        .controller(IdleController::new())
+        .controller(AutosaveController::new())
 }
 
 fn menu(_id: Option<WindowId>, _state: &AppState, _env: &Env) -> Menu<AppState> {
@@ -24,11 +38,35 @@ fn menu(_id: Option<WindowId>, _state: &AppState, _env: &Env) -> Menu<AppState>
             .entry(MenuItem::new("New file")
                 .command(commands::NEW_FILE)
                 .hotkey(RawMods::Ctrl, "n"))
+            .entry(MenuItem::new("New folder...")
+                .command(crate::commands::SHOW_NEW_FOLDER.with(String::new())))
+            .entry(MenuItem::new("Import...")
+                .command(crate::commands::SHOW_IMPORT))
             .entry(MenuItem::new("Save all")
                 .command(commands::SAVE_FILE)
                 .hotkey(RawMods::Ctrl, "s"))
+            .entry(MenuItem::new("Lock all")
+                .command(crate::commands::LOCK_ALL)
+                .hotkey(RawMods::Ctrl, "l"))
+            .entry(Menu::new("Vault")
+                .entry(MenuItem::new("Create vault...")
+                    .command(crate::commands::SHOW_CREATE_VAULT)
+                    .enabled_if(|state: &AppState, _env| !state.vault.exists()))
+                .entry(MenuItem::new("Unlock vault...")
+                    .command(crate::commands::SHOW_UNLOCK_VAULT)
+                    .enabled_if(|state: &AppState, _env| state.vault.exists() && !state.vault.unlocked()))
+                .entry(MenuItem::new("Change master password...")
+                    .command(crate::commands::SHOW_CHANGE_VAULT_PASSWORD)
+                    .enabled_if(|state: &AppState, _env| state.vault.exists())))
+            .entry(MenuItem::new("Trash")
+                .command(crate::commands::SHOW_TRASH))
+            .entry(MenuItem::new("Recover unsaved...")
+                .command(crate::commands::SHOW_RECOVERY)
+                .enabled_if(|state: &AppState, _env| !state.recovery.is_empty()))
             .entry(MenuItem::new("Backups")
                 .command(crate::commands::SHOW_BACKUPS))
+            .entry(MenuItem::new("Settings")
+                .command(crate::commands::SHOW_SETTINGS))
             .entry(MenuItem::new("Exit")
                 .command(commands::CLOSE_ALL_WINDOWS)))
         .entry(MenuItem::new("About")
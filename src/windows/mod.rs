@@ -7,6 +7,23 @@ pub mod new_file_window;
 pub mod about_window;
 pub mod dialog_window;
 pub mod backup_window;
+pub mod change_password_window;
+pub mod protect_window;
+pub mod remove_protection_window;
+pub mod settings_window;
+pub mod create_vault_window;
+pub mod unlock_vault_window;
+pub mod change_vault_password_window;
+pub mod recovery_code_window;
+pub mod export_window;
+pub mod import_window;
+pub mod generator_window;
+pub mod new_folder_window;
+pub mod move_to_folder_window;
+pub mod rename_tab_window;
+pub mod trash_window;
+pub mod history_window;
+pub mod recovery_window;
 
 pub fn primary_screen_center(size: impl Into<Size>) -> Point {
     let monitors = Screen::get_monitors();
@@ -0,0 +1,54 @@
+use druid::{Widget, WidgetExt, WindowDesc};
+use druid::im::Vector;
+use druid::widget::{Button, CrossAxisAlignment, Flex, Padding, Scroll, ViewSwitcher};
+use crate::{AppState, windows};
+use crate::ui::notebook_tree;
+use crate::windows::primary_screen_center;
+
+pub fn new(key: u64) -> WindowDesc<AppState> {
+    let size = (300.0, 300.0);
+    WindowDesc::new(ui(key))
+        .title("Move to folder")
+        .window_size(size)
+        .resizable(false)
+        .set_position(primary_screen_center(size))
+}
+
+fn ui(key: u64) -> impl Widget<AppState> {
+    let folders = ViewSwitcher::new(
+        |state: &AppState, _env| state.tabs.folders().clone(),
+        move |folders: &Vector<String>, _state, _env| -> Box<dyn Widget<AppState>> {
+            let mut column = Flex::column()
+                .cross_axis_alignment(CrossAxisAlignment::Start)
+                .with_child(folder_button(key, String::new()));
+
+            for folder in folders {
+                column.add_spacer(5.0);
+                column.add_child(folder_button(key, folder.clone()));
+            }
+
+            Box::new(column)
+        });
+
+    Padding::new(5.0, Scroll::new(folders)
+        .vertical()
+        .expand())
+}
+
+fn folder_button(key: u64, folder: String) -> impl Widget<AppState> {
+    let depth = notebook_tree::depth(&folder);
+    let label = notebook_tree::folder_label(&folder).to_string();
+    let current = folder.clone();
+
+    Button::new(label)
+        .on_click(move |ctx, state: &mut AppState, _env| {
+            if let Err(e) = state.tabs.move_tab_to(key, &folder) {
+                ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+            }
+            ctx
+                .window()
+                .close();
+        })
+        .disabled_if(move |state: &AppState, _env| !state.tabs.contains(key) || state.tabs.get(key).folder() == current)
+        .padding((depth as f64 * notebook_tree::INDENT, 0.0, 0.0, 0.0))
+}
\ No newline at end of file
@@ -1,10 +1,13 @@
 use druid::{lens, Widget, WidgetExt, WindowDesc};
-use druid::widget::{Button, Flex, Label, MainAxisAlignment, Padding, SizedBox, Tabs, TextBox};
-use crate::{AppState, windows};
+use druid::widget::{Button, Checkbox, Flex, Label, MainAxisAlignment, Padding, SizedBox, Switch, Tabs, TextBox};
+use crate::{AppState, commands, strength, windows};
+use crate::ui::key_file_picker::key_file_picker;
+use crate::ui::lens::new_tab_to_password_shown_lens::NewTabToPasswordShownLens;
+use crate::windows::generator_window::Output;
 use crate::windows::primary_screen_center;
 
 pub fn new() -> WindowDesc<AppState> {
-    let size = (400.0, 185.0);
+    let size = (400.0, 355.0);
     WindowDesc::new(ui())
         .title("New file")
         .window_size(size)
@@ -18,7 +21,7 @@ fn ui() -> impl Widget<AppState> {
         .with_tab("Protected", protected_ui())
 }
 
-const LABEL_WIDTH : f64 = 80.0;
+const LABEL_WIDTH : f64 = 110.0;
 
 fn clear_ui() -> impl Widget<AppState> {
     Padding::new(5.0, Flex::column()
@@ -62,20 +65,79 @@ fn protected_ui() -> impl Widget<AppState> {
             .with_spacer(5.0)
             .with_flex_child(TextBox::new()
                 .expand_width()
-                .lens(lens!(AppState, new_tab.password)), 1.0))
+                .lens(lens!(AppState, new_tab.password)), 1.0)
+            .with_spacer(5.0)
+            .with_child(Checkbox::new("Show")
+                .lens(NewTabToPasswordShownLens::new())
+                .lens(lens!(AppState, new_tab))))
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .with_child(SizedBox::new(Label::new("Repeat password:"))
+                .width(LABEL_WIDTH))
+            .with_spacer(5.0)
+            .with_flex_child(TextBox::new()
+                .expand_width()
+                .lens(lens!(AppState, new_tab.repeat)), 1.0))
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .with_child(SizedBox::new(Label::new("Strength:"))
+                .width(LABEL_WIDTH))
+            .with_spacer(5.0)
+            .with_flex_child(Label::dynamic(|state: &AppState, _env| password_status(state))
+                .expand_width(), 1.0))
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .with_child(SizedBox::new(Label::new("Key file:"))
+                .width(LABEL_WIDTH))
+            .with_spacer(5.0)
+            .with_flex_child(key_file_picker()
+                .lens(lens!(AppState, new_tab.key_file)), 1.0))
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .with_child(SizedBox::new(Label::new("Vault:"))
+                .width(LABEL_WIDTH))
+            .with_spacer(5.0)
+            .with_child(Switch::new()
+                .lens(lens!(AppState, new_tab.vault))
+                .disabled_if(|state: &AppState, _env| !state.vault.unlocked())))
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .with_child(SizedBox::new(Label::new("Hide name:"))
+                .width(LABEL_WIDTH))
+            .with_spacer(5.0)
+            .with_child(Switch::new()
+                .lens(lens!(AppState, new_tab.hide_name))))
         .with_spacer(5.0)
         .with_child(Flex::row()
             .main_axis_alignment(MainAxisAlignment::End)
+            .with_child(SizedBox::new(Button::new("Generate...")
+                .on_click(|ctx, _state: &mut AppState, _env| {
+                    ctx.submit_command(commands::SHOW_GENERATOR.with(Output::NewTab));
+                }))
+                .width(100.0)
+                .height(30.0))
+            .with_spacer(5.0)
             .with_child(SizedBox::new(Button::new("Ok")
                 .on_click(|ctx, state: &mut AppState, _env| {
-                    if let Err(e) = state.add_new_protected_tab() {
-                        ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                    match state.add_new_protected_tab() {
+                        Ok(code) => ctx.new_window(windows::recovery_code_window::new(&code)),
+                        Err(e) => ctx.new_window(windows::information_window::new(format!("Error: {}", e)))
                     };
                     ctx
                         .window()
                         .close();
-                }))
+                })
+                .disabled_if(|state: &AppState, _env| state.check_new_tab_password().is_err()))
                 .width(70.0)
                 .height(30.0))
             .expand_width()))
+}
+
+fn password_status(state: &AppState) -> String {
+    let password = state.new_tab.password.value();
+    let strength = strength::describe(strength::score(password));
+    match state.check_new_tab_password() {
+        Ok(_) => strength.to_string(),
+        Err(e) => format!("{} ({})", strength, e)
+    }
 }
\ No newline at end of file
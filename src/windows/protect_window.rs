@@ -0,0 +1,51 @@
+use druid::{lens, Widget, WidgetExt, WindowDesc};
+use druid::widget::{Button, Flex, Label, MainAxisAlignment, Padding, SizedBox, TextBox};
+use crate::{AppState, windows};
+use crate::windows::primary_screen_center;
+
+pub fn new(key: u64) -> WindowDesc<AppState> {
+    let size = (400.0, 120.0);
+    WindowDesc::new(ui(key))
+        .title("Protect")
+        .window_size(size)
+        .resizable(false)
+        .set_position(primary_screen_center(size))
+}
+
+const LABEL_WIDTH : f64 = 120.0;
+
+fn ui(key: u64) -> impl Widget<AppState> {
+    Padding::new(5.0, Flex::column()
+        .with_child(Flex::row()
+            .with_child(SizedBox::new(Label::new("Password:"))
+                .width(LABEL_WIDTH))
+            .with_spacer(5.0)
+            .with_flex_child(TextBox::new()
+                .expand_width()
+                .lens(lens!(AppState, password_form.password)), 1.0))
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .with_child(SizedBox::new(Label::new("Repeat password:"))
+                .width(LABEL_WIDTH))
+            .with_spacer(5.0)
+            .with_flex_child(TextBox::new()
+                .expand_width()
+                .lens(lens!(AppState, password_form.repeat)), 1.0))
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .main_axis_alignment(MainAxisAlignment::End)
+            .with_child(SizedBox::new(Button::new("Ok")
+                .on_click(move |ctx, state: &mut AppState, _env| {
+                    match state.protect_tab(key) {
+                        Ok(code) => ctx.new_window(windows::recovery_code_window::new(&code)),
+                        Err(e) => ctx.new_window(windows::information_window::new(format!("Error: {}", e)))
+                    };
+                    ctx
+                        .window()
+                        .close();
+                })
+                .disabled_if(move |state: &AppState, _env| !state.tabs.contains(key)))
+                .width(70.0)
+                .height(30.0))
+            .expand_width()))
+}
\ No newline at end of file
@@ -0,0 +1,50 @@
+use druid::{Application, FontDescriptor, FontFamily, Widget, WidgetExt, WindowDesc};
+use druid::widget::{Button, CrossAxisAlignment, Flex, Label, LineBreaking, MainAxisAlignment, Padding, SizedBox};
+use crate::AppState;
+use crate::secret::Secret;
+use crate::windows::primary_screen_center;
+
+pub fn new(code: &Secret<String>) -> WindowDesc<AppState> {
+    let size = (480.0, 190.0);
+    WindowDesc::new(ui(code.clone()))
+        .title("Recovery code")
+        .window_size(size)
+        .resizable(false)
+        .set_position(primary_screen_center(size))
+}
+
+// Code is kept as secret by the copy button, so it is wiped when window is closed
+fn ui(code: Secret<String>) -> impl Widget<AppState> {
+    let font = FontDescriptor::new(FontFamily::MONOSPACE)
+        .with_size(18.0);
+
+    Padding::new(10.0, Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::End)
+        .with_child(Label::new("Write this code down and keep it safe. It opens the file if password is lost and won't be shown again.")
+            .with_line_break_mode(LineBreaking::WordWrap)
+            .expand_width())
+        .with_spacer(10.0)
+        .with_flex_child(Label::new(code.expose().as_str())
+            .with_font(font)
+            .center(), 1.0)
+        .with_spacer(10.0)
+        .with_child(Flex::row()
+            .main_axis_alignment(MainAxisAlignment::End)
+            .with_child(SizedBox::new(Button::new("Copy")
+                .on_click(move |_ctx, _state: &mut AppState, _env| {
+                    Application::global()
+                        .clipboard()
+                        .put_string(code.expose());
+                }))
+                .width(80.0)
+                .height(30.0))
+            .with_spacer(5.0)
+            .with_child(SizedBox::new(Button::new("Ok")
+                .on_click(|ctx, _state: &mut AppState, _env| {
+                    ctx
+                        .window()
+                        .close()
+                }))
+                .width(80.0)
+                .height(30.0))))
+}
\ No newline at end of file
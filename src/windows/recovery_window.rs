@@ -0,0 +1,123 @@
+use chrono::Local;
+use druid::{lens, FontDescriptor, FontFamily, Widget, WidgetExt, WindowDesc};
+use druid::im::Vector;
+use druid::widget::{Button, CrossAxisAlignment, Flex, Label, LineBreaking, MainAxisAlignment, Padding, RadioGroup, Scroll, SizedBox, ViewSwitcher};
+use crate::{AppState, windows};
+use crate::state::history;
+use crate::state::journal::{self, Entry};
+use crate::windows::primary_screen_center;
+
+pub fn new() -> WindowDesc<AppState> {
+    let size = (700.0, 450.0);
+    WindowDesc::new(ui())
+        .title("Recover unsaved notes")
+        .window_size(size)
+        .set_position(primary_screen_center(size))
+}
+
+fn ui() -> impl Widget<AppState> {
+    let entries = ViewSwitcher::new(
+        |state: &AppState, _env| state.recovery.clone(),
+        |entries: &Vector<Entry>, _state, _env| -> Box<dyn Widget<AppState>> {
+            if entries.is_empty() {
+                return Box::new(Label::new("Nothing to recover"));
+            }
+
+            let items: Vec<(String, Option<String>)> = entries
+                .iter()
+                .map(|e| {
+                    let modified = e.modified
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M");
+                    (format!("{} ({})", e.label, modified), Some(e.id.clone()))
+                })
+                .collect();
+
+            Box::new(RadioGroup::column(items)
+                .lens(lens!(AppState, selected_recovery)))
+        });
+
+    // Rebuilt when note is unlocked or edited
+    let diff = ViewSwitcher::new(
+        |state: &AppState, _env| {
+            let tab = state.selected_recovery
+                .as_ref()
+                .and_then(|id| state.recovery_entry(id).ok())
+                .and_then(|e| state.tabs.find(&e.file))
+                .map(|key| state.tabs.get(key))
+                .map(|tab| (tab.opened(), tab.meta.modified));
+            (state.selected_recovery.clone(), tab)
+        },
+        |(selected, _), state: &AppState, _env| -> Box<dyn Widget<AppState>> {
+            let text = match selected {
+                Some(id) => compare(state, id),
+                None => String::new()
+            };
+
+            let font = FontDescriptor::new(FontFamily::MONOSPACE)
+                .with_size(13.0);
+            Box::new(Label::new(text)
+                .with_font(font)
+                .with_line_break_mode(LineBreaking::Overflow))
+        });
+
+    Padding::new(5.0, Flex::column()
+        .with_flex_child(Flex::row()
+            .cross_axis_alignment(CrossAxisAlignment::Start)
+            .with_child(SizedBox::new(Scroll::new(entries)
+                .vertical())
+                .width(220.0)
+                .expand_height())
+            .with_spacer(5.0)
+            .with_flex_child(Scroll::new(diff)
+                .expand(), 1.0), 1.0)
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .main_axis_alignment(MainAxisAlignment::SpaceBetween)
+            .with_child(Label::new("- saved text, + unsaved text"))
+            .with_child(Flex::row()
+                .with_child(SizedBox::new(Button::new("Discard")
+                    .on_click(|ctx, state: &mut AppState, _env| {
+                        if let Some(id) = state.selected_recovery.clone() {
+                            if let Err(e) = state.discard_recovery(&id) {
+                                ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                            }
+                        }
+                    })
+                    .disabled_if(|state: &AppState, _env| state.selected_recovery.is_none()))
+                    .width(80.0)
+                    .height(30.0))
+                .with_spacer(5.0)
+                .with_child(SizedBox::new(Button::new("Recover")
+                    .on_click(|ctx, state: &mut AppState, _env| {
+                        if let Some(id) = state.selected_recovery.clone() {
+                            if let Err(e) = state.recover_note(&id) {
+                                ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                            }
+                        }
+                    })
+                    .disabled_if(|state: &AppState, _env| state.selected_recovery.is_none()))
+                    .width(80.0)
+                    .height(30.0)))
+            .expand_width()))
+}
+
+fn compare(state: &AppState, id: &str) -> String {
+    let tab = match state.recovery_entry(id).ok().and_then(|e| state.tabs.find(&e.file)) {
+        Some(key) => state.tabs.get(key),
+        None => return "Note not found, it may be removed or renamed".to_string()
+    };
+
+    if !tab.opened() {
+        return "Unlock note to compare and recover it".to_string();
+    }
+
+    let path = match journal::recovery_path(id) {
+        Ok(path) => path,
+        Err(e) => return format!("Error: {}", e)
+    };
+    match tab.read_copy(&path) {
+        Ok(unsaved) => history::diff(tab.text().unwrap_or_default(), unsaved.expose()),
+        Err(e) => format!("Error: {}", e)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,99 @@
+use druid::{lens, Widget, WidgetExt, WindowDesc};
+use druid::text::ParseFormatter;
+use druid::widget::{Button, CrossAxisAlignment, Flex, Label, Padding, SizedBox, Switch, TextBox};
+use crate::{AppState, windows};
+use crate::windows::primary_screen_center;
+
+pub fn new() -> WindowDesc<AppState> {
+    let size = (400.0, 370.0);
+    WindowDesc::new(ui())
+        .title("Settings")
+        .window_size(size)
+        .resizable(false)
+        .set_position(primary_screen_center(size))
+}
+
+pub fn ui() -> impl Widget<AppState> {
+    Padding::new(10.0, Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::End)
+        .with_child(Flex::row()
+            .with_flex_child(Label::new("Autosave every, seconds (0 - off)").expand_width(), 1.0)
+            .with_spacer(10.0)
+            .with_child(SizedBox::new(TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .update_data_while_editing(true)
+                .lens(lens!(AppState, config.autosave_seconds)))
+                .width(60.0)))
+        .with_spacer(10.0)
+        .with_child(Flex::row()
+            .with_flex_child(Label::new("Lock after idle, minutes (0 - never)").expand_width(), 1.0)
+            .with_spacer(10.0)
+            .with_child(SizedBox::new(TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .update_data_while_editing(true)
+                .lens(lens!(AppState, config.auto_lock_minutes)))
+                .width(60.0)))
+        .with_spacer(10.0)
+        .with_child(Flex::row()
+            .with_flex_child(Label::new("Lock when minimized").expand_width(), 1.0)
+            .with_spacer(10.0)
+            .with_child(Switch::new()
+                .lens(lens!(AppState, config.lock_on_minimize))))
+        .with_spacer(10.0)
+        .with_child(Flex::row()
+            .with_flex_child(Label::new("Minimum password length").expand_width(), 1.0)
+            .with_spacer(10.0)
+            .with_child(SizedBox::new(TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .update_data_while_editing(true)
+                .lens(lens!(AppState, config.min_password_length)))
+                .width(60.0)))
+        .with_spacer(10.0)
+        .with_child(Flex::row()
+            .with_flex_child(Label::new("Minimum password strength (0 - 4)").expand_width(), 1.0)
+            .with_spacer(10.0)
+            .with_child(SizedBox::new(TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .update_data_while_editing(true)
+                .lens(lens!(AppState, config.min_password_score)))
+                .width(60.0)))
+        .with_spacer(10.0)
+        .with_child(Flex::row()
+            .with_flex_child(Label::new("Keep removed notes, days (0 - forever)").expand_width(), 1.0)
+            .with_spacer(10.0)
+            .with_child(SizedBox::new(TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .update_data_while_editing(true)
+                .lens(lens!(AppState, config.auto_purge_days)))
+                .width(60.0)))
+        .with_spacer(10.0)
+        .with_child(Flex::row()
+            .with_flex_child(Label::new("Versions kept for each note (0 - off)").expand_width(), 1.0)
+            .with_spacer(10.0)
+            .with_child(SizedBox::new(TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .update_data_while_editing(true)
+                .lens(lens!(AppState, config.history_versions)))
+                .width(60.0)))
+        .with_spacer(10.0)
+        .with_child(Flex::row()
+            .with_flex_child(Label::new("Keep versions, days (0 - forever)").expand_width(), 1.0)
+            .with_spacer(10.0)
+            .with_child(SizedBox::new(TextBox::new()
+                .with_formatter(ParseFormatter::new())
+                .update_data_while_editing(true)
+                .lens(lens!(AppState, config.history_days)))
+                .width(60.0)))
+        .with_spacer(10.0)
+        .with_child(Flex::row()
+            .with_child(SizedBox::new(Button::new("Ok")
+                .on_click(|cx, state: &mut AppState, _env| {
+                    if let Err(e) = state.config.save() {
+                        let message = format!("Cannot save configs: {}", e);
+                        cx.new_window(windows::information_window::new(message));
+                    }
+                    cx.window().close()
+                }))
+                .width(80.0)
+                .height(30.0))))
+}
\ No newline at end of file
@@ -0,0 +1,93 @@
+use chrono::Local;
+use druid::{Widget, WidgetExt, WindowDesc};
+use druid::widget::{Button, CrossAxisAlignment, Flex, Label, MainAxisAlignment, Padding, Scroll, SizedBox, ViewSwitcher};
+use crate::{AppState, windows};
+use crate::state::trash::{Trash, TrashItem};
+use crate::windows::primary_screen_center;
+
+pub fn new() -> WindowDesc<AppState> {
+    let size = (500.0, 350.0);
+    WindowDesc::new(ui())
+        .title("Trash")
+        .window_size(size)
+        .resizable(false)
+        .set_position(primary_screen_center(size))
+}
+
+fn ui() -> impl Widget<AppState> {
+    let items = ViewSwitcher::new(
+        |state: &AppState, _env| state.trash.clone(),
+        |trash: &Trash, _state, _env| -> Box<dyn Widget<AppState>> {
+            if trash.is_empty() {
+                return Box::new(Label::new("Trash is empty"));
+            }
+
+            let mut column = Flex::column()
+                .cross_axis_alignment(CrossAxisAlignment::Start);
+            for item in trash.items() {
+                column.add_child(item_row(item));
+                column.add_spacer(5.0);
+            }
+            Box::new(column)
+        });
+
+    Padding::new(5.0, Flex::column()
+        .with_flex_child(Scroll::new(items)
+            .vertical()
+            .expand(), 1.0)
+        .with_spacer(5.0)
+        .with_child(Flex::row()
+            .main_axis_alignment(MainAxisAlignment::End)
+            .with_child(SizedBox::new(Button::new("Empty trash")
+                .on_click(|ctx, _state: &mut AppState, _env| {
+                    ctx.new_window(windows::dialog_window::new(
+                        "Purge all notes in trash? They can't be restored.",
+                        |ctx, state: &mut AppState, _env| {
+                            if let Err(e) = state.trash.purge_all() {
+                                ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                            }
+                        }));
+                })
+                .disabled_if(|state: &AppState, _env| state.trash.is_empty()))
+                .width(100.0)
+                .height(30.0))
+            .expand_width()))
+}
+
+fn item_row(item: TrashItem) -> impl Widget<AppState> {
+    let deleted = item.deleted
+        .with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M");
+    let folder = item.file
+        .rsplit_once('/')
+        .map_or(String::new(), |(folder, _)| format!("{}/", folder));
+    let restore_id = item.id.clone();
+    let purge_id = item.id;
+
+    Flex::row()
+        .with_flex_child(Label::new(format!("{}{}  ({})", folder, item.label, deleted))
+            .expand_width(), 1.0)
+        .with_spacer(5.0)
+        .with_child(SizedBox::new(Button::new("Restore")
+            .on_click(move |ctx, state: &mut AppState, _env| {
+                if let Err(e) = state.restore_from_trash(&restore_id) {
+                    ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                }
+            }))
+            .width(70.0)
+            .height(30.0))
+        .with_spacer(5.0)
+        .with_child(SizedBox::new(Button::new("Purge")
+            .on_click(move |ctx, _state: &mut AppState, _env| {
+                let id = purge_id.clone();
+                ctx.new_window(windows::dialog_window::new(
+                    "Purge note? It can't be restored.",
+                    move |ctx, state: &mut AppState, _env| {
+                        if let Err(e) = state.trash.purge(&id) {
+                            ctx.new_window(windows::information_window::new(format!("Error: {}", e)));
+                        }
+                    }));
+            }))
+            .width(70.0)
+            .height(30.0))
+}
\ No newline at end of file